@@ -1,17 +1,19 @@
 use std::path::PathBuf;
 // This example utilizes the `async` feature
 use texcore::Level::Meta;
-use texcore::{Chapter, ElementList, Elements, Input, Metadata, Part};
-use tokio::io::Result;
+use texcore::{Chapter, ElementList, Elements, Error, Input, Metadata, Part};
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<(), Error> {
     let part = Part::new("part 1");
     let chapter = Chapter::new("chapter 1");
-    // for simplicity we will use default metadata
-    let mut metadata = Metadata::default();
     // we need to make sure document class is book
-    metadata.doc_class = "book".to_string();
+    let metadata = Metadata::builder()
+        .doc_class("book")
+        .title("title")
+        .author("author")
+        .maketitle(true)
+        .build();
 
     // push elements to list
     let mut list = ElementList::new(&metadata);
@@ -1,4 +1,3 @@
-use std::io::Error;
 use std::path::PathBuf;
 
 use texcore::Elements;
@@ -24,7 +23,7 @@ fn main() -> Result<(), Error> {
         PathBuf::from("main.tex"),
         PathBuf::from("structure.tex"),
         input,
-    );
+    )?;
     // To print:
     list.print_tex();
     Ok(())
@@ -1,8 +1,7 @@
-use std::io::Result;
 use std::path::PathBuf;
 use texcore::bundle::tables::*;
 use texcore::TextType::Normal;
-use texcore::{Element, ElementList, Elements, Metadata, Text};
+use texcore::{Element, ElementList, Elements, Error, Metadata, Text};
 
 fn rows() -> Vec<Row> {
     let row_els = vec![
@@ -28,7 +27,7 @@ fn columns() -> Vec<Column> {
     ]
 }
 
-fn main() -> Result<()> {
+fn main() -> Result<(), Error> {
     let table = Table::new(None, columns(), rows(), false, false);
     let mut list = ElementList::new(&Metadata::default());
     list.push(Element::from(table));
@@ -0,0 +1,421 @@
+use crate::Level::*;
+use crate::Type::*;
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::fs::write;
+use std::path::PathBuf;
+
+/// The types of BibTeX entries
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub enum EntryType {
+    Article,
+    Book,
+    Booklet,
+    InBook,
+    InCollection,
+    InProceedings,
+    Manual,
+    MastersThesis,
+    PhdThesis,
+    TechReport,
+    Unpublished,
+    Online,
+    Misc,
+}
+
+impl Tex for EntryType {
+    fn to_latex_string(&self) -> String {
+        match self {
+            EntryType::Article => "article",
+            EntryType::Book => "book",
+            EntryType::Booklet => "booklet",
+            EntryType::InBook => "inbook",
+            EntryType::InCollection => "incollection",
+            EntryType::InProceedings => "inproceedings",
+            EntryType::Manual => "manual",
+            EntryType::MastersThesis => "mastersthesis",
+            EntryType::PhdThesis => "phdthesis",
+            EntryType::TechReport => "techreport",
+            EntryType::Unpublished => "unpublished",
+            EntryType::Online => "online",
+            EntryType::Misc => "misc",
+        }
+        .to_string()
+    }
+}
+
+/// The fields of a BibTeX entry
+#[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub enum Field {
+    Author,
+    Editor,
+    Title,
+    BookTitle,
+    Journal,
+    Year,
+    Month,
+    Volume,
+    Number,
+    Pages,
+    Edition,
+    Chapter,
+    Publisher,
+    Address,
+    Institution,
+    School,
+    Organization,
+    HowPublished,
+    Doi,
+    Isbn,
+    Url,
+    Note,
+    /// Any other field
+    Custom(String),
+}
+
+impl Tex for Field {
+    fn to_latex_string(&self) -> String {
+        match self {
+            Field::Author => "author",
+            Field::Editor => "editor",
+            Field::Title => "title",
+            Field::BookTitle => "booktitle",
+            Field::Journal => "journal",
+            Field::Year => "year",
+            Field::Month => "month",
+            Field::Volume => "volume",
+            Field::Number => "number",
+            Field::Pages => "pages",
+            Field::Edition => "edition",
+            Field::Chapter => "chapter",
+            Field::Publisher => "publisher",
+            Field::Address => "address",
+            Field::Institution => "institution",
+            Field::School => "school",
+            Field::Organization => "organization",
+            Field::HowPublished => "howpublished",
+            Field::Doi => "doi",
+            Field::Isbn => "isbn",
+            Field::Url => "url",
+            Field::Note => "note",
+            Field::Custom(field) => field,
+        }
+        .to_string()
+    }
+}
+
+/// A BibTeX entry, where field values are written as is between braces
+#[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub struct Entry {
+    pub type_: EntryType,
+    pub key: String,
+    pub fields: Vec<(Field, String)>,
+}
+
+impl Entry {
+    pub fn new(type_: EntryType, key: &str) -> Self {
+        Self {
+            type_,
+            key: key.to_string(),
+            fields: Vec::new(),
+        }
+    }
+    /// Returns the entry with a field set, replacing any previous value
+    pub fn field(mut self, field: Field, value: &str) -> Self {
+        self.set(field, value);
+        self
+    }
+    /// Sets a field, replacing any previous value
+    pub fn set(&mut self, field: Field, value: &str) {
+        match self.fields.iter_mut().find(|(f, _)| *f == field) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.fields.push((field, value.to_string())),
+        }
+    }
+    /// Returns the value of a field
+    pub fn get(&self, field: &Field) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(f, _)| f == field)
+            .map(|(_, v)| v.as_str())
+    }
+    /// Returns the fields BibTeX requires for the entry type that aren't set,
+    /// where `author` is satisfied by `editor` for books
+    pub fn missing_fields(&self) -> Vec<Field> {
+        let required = match self.type_ {
+            EntryType::Article => vec![Field::Author, Field::Title, Field::Journal, Field::Year],
+            EntryType::Book | EntryType::InBook => {
+                vec![Field::Author, Field::Title, Field::Publisher, Field::Year]
+            }
+            EntryType::InCollection | EntryType::InProceedings => {
+                vec![Field::Author, Field::Title, Field::BookTitle, Field::Year]
+            }
+            EntryType::MastersThesis | EntryType::PhdThesis => {
+                vec![Field::Author, Field::Title, Field::School, Field::Year]
+            }
+            EntryType::TechReport => {
+                vec![Field::Author, Field::Title, Field::Institution, Field::Year]
+            }
+            EntryType::Unpublished => vec![Field::Author, Field::Title, Field::Note],
+            EntryType::Booklet | EntryType::Manual | EntryType::Online => vec![Field::Title],
+            EntryType::Misc => Vec::new(),
+        };
+        let book = matches!(self.type_, EntryType::Book | EntryType::InBook);
+        required
+            .into_iter()
+            .filter(|f| self.get(f).is_none())
+            .filter(|f| !(book && *f == Field::Author && self.get(&Field::Editor).is_some()))
+            .collect()
+    }
+}
+
+impl Tex for Entry {
+    fn to_latex_string(&self) -> String {
+        let mut lines = vec![format!("@{}{{{},", self.type_.to_latex_string(), self.key)];
+        for (field, value) in &self.fields {
+            lines.push(format!("  {} = {{{value}}},", field.to_latex_string()))
+        }
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+}
+
+/// A database of BibTeX entries written to a `.bib` file
+#[derive(Debug, Clone, Default, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub struct Bibliography {
+    entries: Vec<Entry>,
+}
+
+impl Bibliography {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Pushes an entry, replacing any entry with the same key
+    pub fn push(&mut self, entry: Entry) {
+        match self.entries.iter_mut().find(|e| e.key == entry.key) {
+            Some(e) => *e = entry,
+            None => self.entries.push(entry),
+        }
+    }
+    /// Pushes an entry vector
+    pub fn push_array(&mut self, entries: Vec<Entry>) {
+        for entry in entries {
+            self.push(entry)
+        }
+    }
+    /// Returns the entry with the key
+    pub fn get(&self, key: &str) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.key == key)
+    }
+    /// Whether an entry has the key
+    pub fn contains(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+    /// Returns the entries
+    pub fn entries(&self) -> &Vec<Entry> {
+        &self.entries
+    }
+    /// Writes the `.bib` file
+    pub fn write(&self, path: PathBuf) -> Result<(), Error> {
+        write(path, self.to_latex_string().as_bytes())?;
+        Ok(())
+    }
+}
+
+impl Tex for Bibliography {
+    fn to_latex_string(&self) -> String {
+        let entries: Vec<String> = self.entries.iter().map(|e| e.to_latex_string()).collect();
+        entries.join("\n\n")
+    }
+}
+
+/// The commands to cite entries, where all but `Cite` and `NoCite` require `biblatex`
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub enum CiteType {
+    /// `\cite{}`
+    Cite,
+    /// `\parencite{}`
+    ParenCite,
+    /// `\textcite{}`
+    TextCite,
+    /// `\autocite{}`
+    AutoCite,
+    /// `\footcite{}`
+    FootCite,
+    /// `\nocite{}`, listing entries without citing them in the text
+    NoCite,
+}
+
+impl CiteType {
+    /// Returns the name of the command
+    pub fn command(&self) -> &'static str {
+        match self {
+            CiteType::Cite => "cite",
+            CiteType::ParenCite => "parencite",
+            CiteType::TextCite => "textcite",
+            CiteType::AutoCite => "autocite",
+            CiteType::FootCite => "footcite",
+            CiteType::NoCite => "nocite",
+        }
+    }
+}
+
+/// Represents a citation like `\cite[p.~5]{knuth84}`.
+///
+/// Use `to_latex_string()` to place it within a `Text::raw()`.
+#[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub struct Citation {
+    pub type_: CiteType,
+    pub keys: Vec<String>,
+    /// Text placed before the citation, only supported by `biblatex`
+    pub prenote: Option<String>,
+    /// Text placed after the citation, like a page number
+    pub postnote: Option<String>,
+}
+
+impl Citation {
+    pub fn new(type_: CiteType, keys: Vec<&str>) -> Self {
+        Self {
+            type_,
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+            prenote: None,
+            postnote: None,
+        }
+    }
+}
+
+impl Tex for Citation {
+    fn to_latex_string(&self) -> String {
+        let notes = match (&self.prenote, &self.postnote) {
+            (Some(pre), Some(post)) => format!("[{pre}][{post}]"),
+            (Some(pre), None) => format!("[{pre}][]"),
+            (None, Some(post)) => format!("[{post}]"),
+            (None, None) => String::new(),
+        };
+        format!(
+            r"\{}{notes}{{{}}}",
+            self.type_.command(),
+            self.keys.join(",")
+        )
+    }
+}
+
+impl Requirements for Citation {
+    fn requirements(&self) -> Vec<Package> {
+        match self.type_ {
+            CiteType::Cite | CiteType::NoCite => Vec::new(),
+            _ => vec![Package::new("biblatex")],
+        }
+    }
+}
+
+impl From<Citation> for Element<Any> {
+    fn from(value: Citation) -> Self {
+        let requires = value.requirements();
+        let latex = value.to_latex_string();
+        let any = Any {
+            value: value.keys.join(","),
+            latex,
+            type_: T_Citation,
+            level: Document,
+            header_level: None,
+            text_type: None,
+            list_type: None,
+            items: None,
+            elements: None,
+            package_options: None,
+            modified: false,
+        };
+        Element::new_any(any).requiring(requires)
+    }
+}
+
+/// The tools used to typeset the bibliography
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub enum Backend {
+    /// `\bibliographystyle{}` and `\bibliography{}`
+    BibTex,
+    /// The `biblatex` package with `\addbibresource{}` and `\printbibliography`
+    BibLatex,
+}
+
+/// Prints the bibliography from a `.bib` file with a style like `plain` (BibTeX) or `authoryear` (biblatex)
+#[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub struct PrintBibliography {
+    pub backend: Backend,
+    pub style: String,
+    pub file: PathBuf,
+}
+
+impl PrintBibliography {
+    pub fn new(backend: Backend, style: &str, file: PathBuf) -> Self {
+        Self {
+            backend,
+            style: style.to_string(),
+            file,
+        }
+    }
+    /// Returns `biblatex` and `\addbibresource{}` for the `BibLatex` backend followed by the bibliography
+    pub fn build(&self) -> Vec<Element<Any>> {
+        let mut elements: Vec<Element<Any>> =
+            self.requirements().into_iter().map(Element::from).collect();
+        if self.backend == Backend::BibLatex {
+            let file = self.file.to_str().unwrap_or("");
+            let latex = format!(r"\addbibresource{{{file}}}");
+            elements.push(Element::from(Custom::new(&latex, Packages)));
+        }
+        elements.push(Element::from(self.clone()));
+        elements
+    }
+}
+
+impl Tex for PrintBibliography {
+    fn to_latex_string(&self) -> String {
+        match self.backend {
+            Backend::BibTex => {
+                // bibtex expects the file without its extension
+                let file = self.file.with_extension("");
+                let file = file.to_str().unwrap_or("");
+                format!(
+                    "\\bibliographystyle{{{}}}\n\\bibliography{{{file}}}",
+                    self.style
+                )
+            }
+            Backend::BibLatex => r"\printbibliography".to_string(),
+        }
+    }
+}
+
+/// Requires `biblatex` loaded with the style for the `BibLatex` backend
+impl Requirements for PrintBibliography {
+    fn requirements(&self) -> Vec<Package> {
+        match self.backend {
+            Backend::BibTex => Vec::new(),
+            Backend::BibLatex => {
+                let style = PackageOption::Value("style".to_string(), self.style.to_string());
+                vec![Package::with_options("biblatex", vec![style])]
+            }
+        }
+    }
+}
+
+impl From<PrintBibliography> for Element<Any> {
+    fn from(value: PrintBibliography) -> Self {
+        let requires = value.requirements();
+        let latex = value.to_latex_string();
+        let any = Any {
+            value: value.file.to_str().unwrap_or("").to_string(),
+            latex,
+            type_: T_Bibliography,
+            level: Document,
+            header_level: None,
+            text_type: None,
+            list_type: None,
+            items: None,
+            elements: None,
+            package_options: None,
+            modified: false,
+        };
+        Element::new_any(any).requiring(requires)
+    }
+}
@@ -1,3 +1,5 @@
+/// Provides `figure` floats with captions, labels and subfigure grids.
+pub mod figure;
 /// Provide types to make adding images in a LaTeX document easier with the `graphicx` package.
 pub mod graphicx;
 /// Provides types to make adding mathematical equations easier with various math related packages.
@@ -6,3 +8,5 @@ pub mod graphicx;
 pub mod math;
 /// Provides types to make tables easily
 pub mod tables;
+/// Provides theorem-like environments from `amsthm`, declaring each kind automatically.
+pub mod theorem;
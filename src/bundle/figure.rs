@@ -0,0 +1,294 @@
+use crate::{escape_unless, Any, Element, Label, Level, Package, Requirements, Tex, Type};
+use serde::{Deserialize, Serialize};
+
+/// The placement specifiers of a float
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Placement {
+    /// `h`, where it appears in the source
+    Here,
+    /// `t`, at the top of a page
+    Top,
+    /// `b`, at the bottom of a page
+    Bottom,
+    /// `p`, on a page of floats
+    Page,
+    /// `!`, overriding the limits latex puts on floats
+    Force,
+    /// `H`, exactly where it appears in the source, requires `float`
+    Exact,
+}
+
+impl Tex for Placement {
+    fn to_latex_string(&self) -> String {
+        match self {
+            Placement::Here => "h",
+            Placement::Top => "t",
+            Placement::Bottom => "b",
+            Placement::Page => "p",
+            Placement::Force => "!",
+            Placement::Exact => "H",
+        }
+        .to_string()
+    }
+}
+
+/// One panel of a figure, rendered with the `subfigure` environment of `subcaption`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubFigure {
+    pub elements: Vec<Element<Any>>,
+    pub caption: Option<String>,
+    pub label: Option<Label>,
+    /// The width as a fraction of `\textwidth`, which is shared out by the figure's columns if unset
+    pub width: Option<f64>,
+    /// Inserts the caption as trusted LaTeX instead of escaping it
+    #[serde(default)]
+    pub(crate) raw: bool,
+}
+
+impl SubFigure {
+    pub fn new(elements: Vec<Element<Any>>) -> Self {
+        Self {
+            elements,
+            caption: None,
+            label: None,
+            width: None,
+            raw: false,
+        }
+    }
+    /// Sets the caption, escaping its LaTeX special characters unless `raw()` is used
+    pub fn caption(mut self, caption: &str) -> Self {
+        self.caption = Some(caption.to_string());
+        self
+    }
+    /// Inserts the caption as trusted LaTeX instead of escaping it
+    pub fn raw(mut self) -> Self {
+        self.raw = true;
+        self
+    }
+    pub fn label(mut self, label: Label) -> Self {
+        self.label = Some(label);
+        self
+    }
+    /// Sets the width as a fraction of `\textwidth`
+    pub fn width(mut self, width: f64) -> Self {
+        self.width = Some(width);
+        self
+    }
+    // the environment, with `width` used when none was set
+    fn latex(&self, width: f64) -> String {
+        let width = self.width.unwrap_or(width);
+        let mut lines = vec![
+            format!(r"\begin{{subfigure}}[b]{{{width}\textwidth}}"),
+            r"\centering".to_string(),
+        ];
+        lines.extend(self.elements.iter().map(|e| e.latex.to_string()));
+        lines.extend(captioned(
+            self.caption.as_deref(),
+            None,
+            self.label.as_ref(),
+            self.raw,
+        ));
+        lines.push(r"\end{subfigure}".to_string());
+        lines.join("\n")
+    }
+}
+
+/// A `figure` float holding images, tables or drawings, with a caption and a label.
+///
+/// Subfigures are laid out in a grid after the elements, `columns` to a row.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Figure {
+    pub elements: Vec<Element<Any>>,
+    pub subfigures: Vec<SubFigure>,
+    pub placement: Vec<Placement>,
+    /// Whether to use `figure*`, spanning both columns of a two column document
+    pub wide: bool,
+    pub centering: bool,
+    pub caption: Option<String>,
+    /// The caption used in the list of figures
+    pub short_caption: Option<String>,
+    pub label: Option<Label>,
+    /// The number of subfigures in a row
+    pub columns: usize,
+    /// Inserts the captions as trusted LaTeX instead of escaping them
+    #[serde(default)]
+    pub(crate) raw: bool,
+}
+
+impl Default for Figure {
+    fn default() -> Self {
+        Self {
+            elements: Vec::new(),
+            subfigures: Vec::new(),
+            placement: Vec::new(),
+            wide: false,
+            centering: true,
+            caption: None,
+            short_caption: None,
+            label: None,
+            columns: 2,
+            raw: false,
+        }
+    }
+}
+
+impl Figure {
+    /// A centered figure with no placement specifiers
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn placement(mut self, placement: Vec<Placement>) -> Self {
+        self.placement = placement;
+        self
+    }
+    /// Uses `figure*`
+    pub fn wide(mut self) -> Self {
+        self.wide = true;
+        self
+    }
+    /// Leaves out `\centering`
+    pub fn uncentered(mut self) -> Self {
+        self.centering = false;
+        self
+    }
+    /// Sets the caption, escaping its LaTeX special characters unless `raw()` is used
+    pub fn caption(mut self, caption: &str) -> Self {
+        self.caption = Some(caption.to_string());
+        self
+    }
+    /// Inserts the caption and the short caption as trusted LaTeX instead of escaping them
+    pub fn raw(mut self) -> Self {
+        self.raw = true;
+        self
+    }
+    /// Sets the caption used in the list of figures, requires a caption
+    pub fn short_caption(mut self, short: &str) -> Self {
+        self.short_caption = Some(short.to_string());
+        self
+    }
+    pub fn label(mut self, label: Label) -> Self {
+        self.label = Some(label);
+        self
+    }
+    /// Sets the number of subfigures in a row, at least 1
+    pub fn columns(mut self, columns: usize) -> Self {
+        self.columns = columns.max(1);
+        self
+    }
+    pub fn push(&mut self, element: Element<Any>) {
+        self.elements.push(element)
+    }
+    pub fn push_array(&mut self, elements: Vec<Element<Any>>) {
+        self.elements.extend(elements)
+    }
+    pub fn push_subfigure(&mut self, subfigure: SubFigure) {
+        self.subfigures.push(subfigure)
+    }
+    fn name(&self) -> &'static str {
+        if self.wide {
+            "figure*"
+        } else {
+            "figure"
+        }
+    }
+}
+
+impl Tex for Figure {
+    fn to_latex_string(&self) -> String {
+        let name = self.name();
+        let placement: String = self.placement.iter().map(|p| p.to_latex_string()).collect();
+        let mut lines = vec![if placement.is_empty() {
+            format!(r"\begin{{{name}}}")
+        } else {
+            format!(r"\begin{{{name}}}[{placement}]")
+        }];
+        if self.centering {
+            lines.push(r"\centering".to_string())
+        }
+        lines.extend(self.elements.iter().map(|e| e.latex.to_string()));
+        // leaves a little space between the panels of a row
+        let columns = self.columns.max(1);
+        let width = ((0.95 / columns as f64) * 100.0).floor() / 100.0;
+        let rows: Vec<String> = self
+            .subfigures
+            .chunks(columns)
+            .map(|row| {
+                let panels: Vec<String> = row.iter().map(|s| s.latex(width)).collect();
+                panels.join("\n\\hfill\n")
+            })
+            .collect();
+        if !rows.is_empty() {
+            lines.push(rows.join("\n\n"))
+        }
+        lines.extend(captioned(
+            self.caption.as_deref(),
+            self.short_caption.as_deref(),
+            self.label.as_ref(),
+            self.raw,
+        ));
+        lines.push(format!(r"\end{{{name}}}"));
+        lines.join("\n")
+    }
+}
+
+/// Requires `subcaption` for subfigures and `float` for the `H` placement
+impl Requirements for Figure {
+    fn requirements(&self) -> Vec<Package> {
+        let mut packages = Vec::new();
+        if !self.subfigures.is_empty() {
+            packages.push(Package::new("subcaption"))
+        }
+        if self.placement.contains(&Placement::Exact) {
+            packages.push(Package::new("float"))
+        }
+        packages
+    }
+}
+
+impl From<Figure> for Element<Any> {
+    fn from(value: Figure) -> Self {
+        let requires = value.requirements();
+        let latex = value.to_latex_string();
+        let name = value.name().to_string();
+        let label = value.label;
+        let mut elements = value.elements;
+        for subfigure in value.subfigures {
+            elements.extend(subfigure.elements)
+        }
+        let any = Any {
+            value: name,
+            latex,
+            type_: Type::T_Environment,
+            level: Level::Document,
+            header_level: None,
+            text_type: None,
+            list_type: None,
+            items: None,
+            elements: Some(elements),
+            package_options: None,
+            modified: false,
+        };
+        Element::new_any(any)
+            .requiring(requires)
+            .labelled_at_last(label)
+    }
+}
+
+// `\caption[{short}]{}` followed by the label, which must come after the caption to refer to it
+fn captioned(
+    caption: Option<&str>,
+    short: Option<&str>,
+    label: Option<&Label>,
+    raw: bool,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    let caption = caption.map(|c| escape_unless(c, raw));
+    let short = short.map(|s| escape_unless(s, raw));
+    match (caption, short) {
+        (Some(caption), Some(short)) => lines.push(format!(r"\caption[{{{short}}}]{{{caption}}}")),
+        (Some(caption), None) => lines.push(format!(r"\caption{{{caption}}}")),
+        _ => {}
+    }
+    lines.extend(label.map(|l| l.to_latex_string()));
+    lines
+}
@@ -1,8 +1,11 @@
 use crate::Type::T_Bundle;
-use crate::{Any, Element, Level, Package, Tex};
+use crate::{Any, Element, Level, Package, Requirements, Tex};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use texcore_traits::{ExtraOptions, Options};
+
+/// Resolution of included images and their intrinsic sizes
+pub mod assets;
+pub use assets::*;
 
 /// Returns the `graphicx` package
 pub fn graphicx_pkg() -> Element<Any> {
@@ -33,8 +36,15 @@ impl Tex for GraphicPath {
     }
 }
 
+impl Requirements for GraphicPath {
+    fn requirements(&self) -> Vec<Package> {
+        vec![Package::new("graphicx")]
+    }
+}
+
 impl From<GraphicPath> for Element<Any> {
     fn from(path: GraphicPath) -> Self {
+        let requires = path.requirements();
         let latex = path.to_latex_string();
         let any = Any {
             value: path.path.to_str().unwrap_or("").to_string(),
@@ -46,22 +56,181 @@ impl From<GraphicPath> for Element<Any> {
             list_type: None,
             items: None,
             elements: None,
+            package_options: None,
             modified: false,
         };
-        Element::new_any(any)
+        Element::new_any(any).requiring(requires)
+    }
+}
+
+/// A length with its unit, like `3cm` or `0.5\textwidth`
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Length {
+    Pt(f64),
+    Mm(f64),
+    Cm(f64),
+    In(f64),
+    Em(f64),
+    Ex(f64),
+    /// A fraction of `\textwidth`
+    TextWidth(f64),
+    /// A fraction of `\linewidth`
+    LineWidth(f64),
+    /// A fraction of `\textheight`
+    TextHeight(f64),
+}
+
+impl Tex for Length {
+    fn to_latex_string(&self) -> String {
+        match self {
+            Length::Pt(v) => format!("{v}pt"),
+            Length::Mm(v) => format!("{v}mm"),
+            Length::Cm(v) => format!("{v}cm"),
+            Length::In(v) => format!("{v}in"),
+            Length::Em(v) => format!("{v}em"),
+            Length::Ex(v) => format!("{v}ex"),
+            Length::TextWidth(v) => format!(r"{v}\textwidth"),
+            Length::LineWidth(v) => format!(r"{v}\linewidth"),
+            Length::TextHeight(v) => format!(r"{v}\textheight"),
+        }
+    }
+}
+
+/// The options of `\includegraphics`, where unset ones are left out
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct GraphicOptions {
+    pub width: Option<Length>,
+    pub height: Option<Length>,
+    pub scale: Option<f64>,
+    /// The rotation in degrees, counterclockwise
+    pub angle: Option<f64>,
+    /// The amounts cut from the left, bottom, right and top
+    pub trim: Option<[Length; 4]>,
+    /// Whether to hide what lies outside the trimmed area
+    pub clip: bool,
+    /// The page of a multi-page PDF
+    pub page: Option<u32>,
+    /// Whether to keep the aspect ratio when both width and height are set
+    pub keep_aspect_ratio: bool,
+}
+
+impl GraphicOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn width(mut self, width: Length) -> Self {
+        self.width = Some(width);
+        self
+    }
+    pub fn height(mut self, height: Length) -> Self {
+        self.height = Some(height);
+        self
+    }
+    pub fn scale(mut self, scale: f64) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+    pub fn angle(mut self, angle: f64) -> Self {
+        self.angle = Some(angle);
+        self
+    }
+    /// Cuts the given amounts from the left, bottom, right and top, hiding them with `clip`
+    pub fn trim(mut self, left: Length, bottom: Length, right: Length, top: Length) -> Self {
+        self.trim = Some([left, bottom, right, top]);
+        self.clip = true;
+        self
+    }
+    pub fn clip(mut self) -> Self {
+        self.clip = true;
+        self
+    }
+    pub fn page(mut self, page: u32) -> Self {
+        self.page = Some(page);
+        self
+    }
+    pub fn keep_aspect_ratio(mut self) -> Self {
+        self.keep_aspect_ratio = true;
+        self
+    }
+    /// Whether no option is set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The options separated by commas, like `width=3cm, angle=90`
+impl Tex for GraphicOptions {
+    fn to_latex_string(&self) -> String {
+        let mut options = Vec::new();
+        if let Some(width) = &self.width {
+            options.push(format!("width={}", width.to_latex_string()))
+        }
+        if let Some(height) = &self.height {
+            options.push(format!("height={}", height.to_latex_string()))
+        }
+        if let Some(scale) = self.scale {
+            options.push(format!("scale={scale}"))
+        }
+        if let Some(angle) = self.angle {
+            options.push(format!("angle={angle}"))
+        }
+        if let Some(trim) = &self.trim {
+            let trim: Vec<String> = trim.iter().map(|l| l.to_latex_string()).collect();
+            options.push(format!("trim={}", trim.join(" ")))
+        }
+        if self.clip {
+            options.push("clip".to_string())
+        }
+        if let Some(page) = self.page {
+            options.push(format!("page={page}"))
+        }
+        if self.keep_aspect_ratio {
+            options.push("keepaspectratio".to_string())
+        }
+        options.join(", ")
     }
 }
 
 /// Used to declare the a path to include an image.
-/// Returns the latex string `\includegraphics{foo.png}`
+/// Returns the latex string `\includegraphics[width=3cm]{foo.png}`
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphicInclude {
     path: PathBuf,
+    #[serde(default)]
+    options: GraphicOptions,
 }
 
 impl GraphicInclude {
     pub fn new(path: PathBuf) -> Self {
-        Self { path }
+        Self {
+            path,
+            options: GraphicOptions::default(),
+        }
+    }
+    /// Includes the image with the given options
+    pub fn with_options(path: PathBuf, options: GraphicOptions) -> Self {
+        Self { path, options }
+    }
+    pub fn options(&self) -> &GraphicOptions {
+        &self.options
+    }
+    /// Shrinks an image of intrinsic `size` to fit within `max`, keeping its aspect ratio,
+    /// and leaves smaller images as they are
+    pub fn fit(mut self, size: ImageSize, max: ImageSize) -> Self {
+        if size.exceeds(&max) {
+            self.options.width = Some(Length::Pt(max.width));
+            self.options.height = Some(Length::Pt(max.height));
+            self.options.keep_aspect_ratio = true;
+        }
+        self
+    }
+    /// Sets the width to `\textwidth` when an image of intrinsic `size` is wider than
+    /// `text_width` points
+    pub fn fit_text_width(mut self, size: ImageSize, text_width: f64) -> Self {
+        if size.width > text_width {
+            self.options.width = Some(Length::TextWidth(1.0));
+        }
+        self
     }
 }
 
@@ -71,12 +240,24 @@ impl Tex for GraphicInclude {
             Some(s) => s.to_string(),
             None => "".to_string(),
         };
-        format!(r"\includegraphics{{{path}}}")
+        if self.options.is_empty() {
+            format!(r"\includegraphics{{{path}}}")
+        } else {
+            let options = self.options.to_latex_string();
+            format!(r"\includegraphics[{options}]{{{path}}}")
+        }
+    }
+}
+
+impl Requirements for GraphicInclude {
+    fn requirements(&self) -> Vec<Package> {
+        vec![Package::new("graphicx")]
     }
 }
 
 impl From<GraphicInclude> for Element<Any> {
     fn from(incl: GraphicInclude) -> Self {
+        let requires = incl.requirements();
         let latex = incl.to_latex_string();
         let any = Any {
             value: incl.path.to_str().unwrap_or("").to_string(),
@@ -88,49 +269,26 @@ impl From<GraphicInclude> for Element<Any> {
             list_type: None,
             items: None,
             elements: None,
+            package_options: None,
             modified: false,
         };
-        Element::new_any(any)
+        Element::new_any(any).requiring(requires)
     }
 }
 
 /// A bundle that will return an array of all images, the graphics path and the package `graphicx`.
-pub fn graphics_bundle(
-    graphic_path: PathBuf,
-    image_paths: Vec<PathBuf>,
-    scales: Vec<f32>,
-) -> Vec<Element<Any>> {
-    let scales: Vec<Options> = {
-        let mut options = Vec::new();
-        for s in scales {
-            let s = format!("scale = {s}");
-            options.push(Options::Square(s))
-        }
-        options
-    };
+pub fn graphics_bundle(graphic_path: PathBuf, images: Vec<GraphicInclude>) -> Vec<Element<Any>> {
     let mut elements = Vec::new();
     elements.push(graphicx_pkg());
     let gp = GraphicPath::new(graphic_path);
     elements.push(Element::from(gp));
-
-    let mut gi_elements = Vec::new();
-
-    for ip in image_paths {
-        let gi = GraphicInclude::new(ip);
-        gi_elements.push(Element::from(gi));
-    }
-
-    for (i, op) in scales.iter().enumerate() {
-        gi_elements[i].modify_element(vec![op.clone()]);
-        elements.push(gi_elements[i].clone())
-    }
-
+    elements.extend(images.into_iter().map(Element::from));
     elements
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::bundle::graphicx::GraphicPath;
+    use crate::bundle::graphicx::*;
     use crate::Tex;
     use std::path::PathBuf;
 
@@ -140,4 +298,31 @@ mod tests {
         let s = r"\graphicspath{ {./images/} }";
         assert_eq!(s.to_string(), gp.to_latex_string())
     }
+
+    #[test]
+    fn test_options() {
+        let options = GraphicOptions::new()
+            .width(Length::TextWidth(0.8))
+            .angle(90.0)
+            .trim(
+                Length::Cm(1.0),
+                Length::Pt(0.0),
+                Length::Mm(5.0),
+                Length::In(0.5),
+            )
+            .page(2)
+            .keep_aspect_ratio();
+        let gi = GraphicInclude::with_options(PathBuf::from("plot.pdf"), options);
+        let s = r"\includegraphics[width=0.8\textwidth, angle=90, trim=1cm 0pt 5mm 0.5in, clip, page=2, keepaspectratio]{plot.pdf}";
+        assert_eq!(s.to_string(), gi.to_latex_string());
+
+        let images = vec![
+            GraphicInclude::new(PathBuf::from("a.png")),
+            GraphicInclude::with_options(PathBuf::from("b.png"), GraphicOptions::new().scale(0.5)),
+        ];
+        let elements = graphics_bundle(PathBuf::from("./images/"), images);
+        assert_eq!(elements.len(), 4);
+        assert_eq!(elements[2].latex, r"\includegraphics{a.png}");
+        assert_eq!(elements[3].latex, r"\includegraphics[scale=0.5]{b.png}");
+    }
 }
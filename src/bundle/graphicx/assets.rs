@@ -0,0 +1,298 @@
+use crate::parse::{find_commands, find_commands_with_options};
+use crate::{Any, Diagnostic, ElementList, Error};
+use serde::{Deserialize, Serialize};
+use std::fs::read;
+use std::path::{Path, PathBuf};
+
+/// The engines a document is compiled with, which differ in the image formats they read
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Serialize, Deserialize)]
+pub enum Engine {
+    /// `latex`, producing a dvi file
+    Latex,
+    PdfLatex,
+    XeLatex,
+    LuaLatex,
+    /// The XeTeX based engine behind the `compile` feature
+    Tectonic,
+}
+
+impl Engine {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Engine::Latex => "latex",
+            Engine::PdfLatex => "pdflatex",
+            Engine::XeLatex => "xelatex",
+            Engine::LuaLatex => "lualatex",
+            Engine::Tectonic => "tectonic",
+        }
+    }
+    /// Returns the extensions `\includegraphics` tries, in order, for a path without one
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Engine::Latex => &["eps", "ps"],
+            _ => &["pdf", "png", "jpg", "jpeg"],
+        }
+    }
+    /// Whether the engine can include images of the format
+    pub fn supports(&self, format: ImageFormat) -> bool {
+        match self {
+            Engine::Latex => matches!(format, ImageFormat::Eps),
+            _ => matches!(
+                format,
+                ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Pdf
+            ),
+        }
+    }
+}
+
+/// The formats of image files, as told by their extension
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Serialize, Deserialize)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Pdf,
+    /// Encapsulated PostScript, `.eps` and `.ps`
+    Eps,
+    Svg,
+    Gif,
+    Other,
+}
+
+impl ImageFormat {
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("png") => ImageFormat::Png,
+            Some("jpg" | "jpeg") => ImageFormat::Jpeg,
+            Some("pdf") => ImageFormat::Pdf,
+            Some("eps" | "ps") => ImageFormat::Eps,
+            Some("svg") => ImageFormat::Svg,
+            Some("gif") => ImageFormat::Gif,
+            _ => ImageFormat::Other,
+        }
+    }
+}
+
+/// The intrinsic size of an image in points, where pixels are converted
+/// with the resolution stored in the file or 72 dpi like `pdflatex`
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Serialize, Deserialize)]
+pub struct ImageSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl ImageSize {
+    pub fn new(width: f64, height: f64) -> Self {
+        Self { width, height }
+    }
+    /// Whether the image is wider or taller than `max`
+    pub fn exceeds(&self, max: &ImageSize) -> bool {
+        self.width > max.width || self.height > max.height
+    }
+}
+
+/// Reads the intrinsic size of a PNG, JPEG or PDF image from its header.
+///
+/// The size of a PDF is read from its first `/MediaBox` in plain text, so PDFs keeping
+/// their pages in compressed object streams give an error and aren't size checked.
+pub fn image_size(path: &Path) -> Result<ImageSize, Error> {
+    let bytes = read(path)?;
+    let size = match ImageFormat::from_path(path) {
+        ImageFormat::Png => png_size(&bytes),
+        ImageFormat::Jpeg => jpeg_size(&bytes),
+        ImageFormat::Pdf => pdf_size(&bytes),
+        _ => None,
+    };
+    size.ok_or_else(|| Error::Validation(format!("can't read the size of `{}`", path.display())))
+}
+
+// converts pixels to points at `dpi` dots per inch
+fn points(pixels: u32, dpi: f64) -> f64 {
+    pixels as f64 * 72.0 / dpi
+}
+
+fn be_u16(bytes: &[u8], i: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(bytes.get(i..i + 2)?.try_into().ok()?))
+}
+
+fn be_u32(bytes: &[u8], i: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(i..i + 4)?.try_into().ok()?))
+}
+
+// the size from the `IHDR` chunk and the resolution from a `pHYs` chunk in pixels per meter
+fn png_size(bytes: &[u8]) -> Option<ImageSize> {
+    if !bytes.starts_with(b"\x89PNG\r\n\x1a\n") || bytes.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    let (width, height) = (be_u32(bytes, 16)?, be_u32(bytes, 20)?);
+    let mut dpi = (72.0, 72.0);
+    let mut i = 8;
+    while let (Some(len), Some(kind)) = (be_u32(bytes, i), bytes.get(i + 4..i + 8)) {
+        match kind {
+            b"pHYs" if bytes.get(i + 16) == Some(&1) => {
+                let x = be_u32(bytes, i + 8)? as f64 * 0.0254;
+                let y = be_u32(bytes, i + 12)? as f64 * 0.0254;
+                if x > 0.0 && y > 0.0 {
+                    dpi = (x, y)
+                }
+            }
+            b"IDAT" | b"IEND" => break,
+            _ => {}
+        }
+        i += 12 + len as usize
+    }
+    Some(ImageSize::new(points(width, dpi.0), points(height, dpi.1)))
+}
+
+// the size from the start of frame and the resolution from the JFIF header
+fn jpeg_size(bytes: &[u8]) -> Option<ImageSize> {
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut dpi = (72.0, 72.0);
+    let mut i = 2;
+    while bytes.get(i) == Some(&0xFF) {
+        let marker = *bytes.get(i + 1)?;
+        let len = be_u16(bytes, i + 2)? as usize;
+        let data = i + 4;
+        match marker {
+            0xE0 if bytes.get(data..data + 5) == Some(b"JFIF\0") => {
+                let (x, y) = (be_u16(bytes, data + 8)?, be_u16(bytes, data + 10)?);
+                let scale = match bytes.get(data + 7) {
+                    Some(1) => 1.0,
+                    Some(2) => 2.54,
+                    _ => 0.0,
+                };
+                if scale > 0.0 && x > 0 && y > 0 {
+                    dpi = (x as f64 * scale, y as f64 * scale)
+                }
+            }
+            // every start of frame except the huffman, arithmetic and restart tables
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                let (height, width) = (be_u16(bytes, data + 1)?, be_u16(bytes, data + 3)?);
+                return Some(ImageSize::new(
+                    points(width as u32, dpi.0),
+                    points(height as u32, dpi.1),
+                ));
+            }
+            _ => {}
+        }
+        i += 2 + len
+    }
+    None
+}
+
+// the size of the first `/MediaBox`, which is in points already,
+// or nothing when it is in a compressed object stream
+fn pdf_size(bytes: &[u8]) -> Option<ImageSize> {
+    if !bytes.starts_with(b"%PDF") {
+        return None;
+    }
+    let text = String::from_utf8_lossy(bytes);
+    let start = text.find("/MediaBox")?;
+    let open = start + text[start..].find('[')?;
+    let close = open + text[open..].find(']')?;
+    let corners: Vec<f64> = text[open + 1..close]
+        .split_whitespace()
+        .map(|n| n.parse().ok())
+        .collect::<Option<_>>()?;
+    match corners[..] {
+        [x0, y0, x1, y1] => Some(ImageSize::new((x1 - x0).abs(), (y1 - y0).abs())),
+        _ => None,
+    }
+}
+
+// the options that set the size of an image
+const SIZE_OPTIONS: [&str; 5] = ["width", "height", "scale", "totalheight", "viewport"];
+
+impl ElementList<Any> {
+    /// Returns the directories declared with `\graphicspath`, in order
+    pub fn graphics_paths(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        for element in self.iter() {
+            for (_, arg) in find_commands(&element.latex, &["graphicspath"]) {
+                let dirs = arg
+                    .split(['{', '}'])
+                    .map(str::trim)
+                    .filter(|d| !d.is_empty());
+                paths.extend(dirs.map(PathBuf::from))
+            }
+        }
+        paths
+    }
+    /// Returns every image included with `\includegraphics` and the file it resolves to,
+    /// looking in `root` and then in the `\graphicspath` directories relative to it,
+    /// and trying the engine's extensions for paths without one
+    pub fn image_assets(&self, root: &Path, engine: Engine) -> Vec<(String, Option<PathBuf>)> {
+        let mut dirs = vec![root.to_path_buf()];
+        dirs.extend(self.graphics_paths().iter().map(|p| root.join(p)));
+        let mut assets = Vec::new();
+        for element in self.iter() {
+            for (_, path) in find_commands_with_options(&element.latex, &["includegraphics"]) {
+                let path = path.trim();
+                let names: Vec<PathBuf> = if Path::new(path).extension().is_some() {
+                    vec![PathBuf::from(path)]
+                } else {
+                    let extensions = engine.extensions().iter();
+                    extensions
+                        .map(|e| Path::new(path).with_extension(e))
+                        .collect()
+                };
+                let file = dirs
+                    .iter()
+                    .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+                    .find(|file| file.is_file());
+                assets.push((path.to_string(), file))
+            }
+        }
+        assets
+    }
+    /// Returns the included images that are missing or in a format the engine can't read,
+    /// resolved like `image_assets()`.
+    ///
+    /// With `max_size`, images without a size option that are larger than it are reported too,
+    /// except the ones whose size can't be read, like PDFs with a compressed `/MediaBox`.
+    pub fn check_assets(
+        &self,
+        root: &Path,
+        engine: Engine,
+        max_size: Option<ImageSize>,
+    ) -> Vec<Diagnostic> {
+        let sized: Vec<bool> = self
+            .iter()
+            .flat_map(|e| find_commands_with_options(&e.latex, &["includegraphics"]))
+            .map(|(options, _)| {
+                let keys = options.unwrap_or("").split(',');
+                let mut keys = keys.map(|o| o.split('=').next().unwrap_or("").trim());
+                keys.any(|k| SIZE_OPTIONS.contains(&k))
+            })
+            .collect();
+        let mut diagnostics = Vec::new();
+        for ((path, file), sized) in self.image_assets(root, engine).into_iter().zip(sized) {
+            let file = match file {
+                Some(file) => file,
+                None => {
+                    diagnostics.push(Diagnostic::MissingImage(path));
+                    continue;
+                }
+            };
+            if !engine.supports(ImageFormat::from_path(&file)) {
+                diagnostics.push(Diagnostic::UnsupportedImage(path, engine));
+                continue;
+            }
+            if let (Some(max), false) = (&max_size, sized) {
+                // images whose size can't be read aren't checked
+                match image_size(&file) {
+                    Ok(size) if size.exceeds(max) => {
+                        diagnostics.push(Diagnostic::OversizedImage(path, size))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        diagnostics
+    }
+}
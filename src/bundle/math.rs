@@ -1,6 +1,28 @@
-use crate::{Any, Element, Environment, Level, Tex, Type};
+use crate::{Any, Element, Environment, Level, Package, Requirements, Tex, Type};
 use serde::{Deserialize, Serialize};
 
+/// Math expressions built from symbols, numbers and variables with operator overloading
+pub mod expr;
+pub use expr::*;
+/// Multi-line math environments like `align` and `cases`
+pub mod multiline;
+pub use multiline::*;
+/// Matrices and arrays rendered from row-major data
+pub mod matrix;
+pub use matrix::*;
+/// The catalogue of math symbols with a lookup by name
+pub mod symbols;
+pub use symbols::*;
+/// Conversion of math written with unicode characters into latex
+pub mod unicode;
+pub use unicode::*;
+/// Presentation MathML for the math types, alongside their latex
+pub mod mathml;
+pub use mathml::MathMl;
+/// Checks of latex math before it is compiled
+pub mod check;
+pub use check::*;
+
 /// Greek letters symbols
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum Greek {
@@ -34,8 +56,18 @@ pub enum Greek {
     Omega(Case),
 }
 
+/// The packages of the letter in the symbol catalogue, if it isn't built into LaTeX
+impl Requirements for Greek {
+    fn requirements(&self) -> Vec<Package> {
+        lookup(&self.to_latex_string())
+            .map(|s| s.requirements())
+            .unwrap_or_default()
+    }
+}
+
 impl From<Greek> for Element<Any> {
     fn from(value: Greek) -> Self {
+        let requires = value.requirements();
         let latex = value.to_latex_string();
         let any = Any {
             value: String::new(),
@@ -47,9 +79,10 @@ impl From<Greek> for Element<Any> {
             list_type: None,
             items: None,
             elements: None,
+            package_options: None,
             modified: false,
         };
-        Element::new_any(any)
+        Element::new_any(any).requiring(requires)
     }
 }
 
@@ -206,8 +239,18 @@ pub enum Arrows {
     RightHarpoonDown,
 }
 
+/// The packages of the arrow in the symbol catalogue, if it isn't built into LaTeX
+impl Requirements for Arrows {
+    fn requirements(&self) -> Vec<Package> {
+        lookup(&self.to_latex_string())
+            .map(|s| s.requirements())
+            .unwrap_or_default()
+    }
+}
+
 impl From<Arrows> for Element<Any> {
     fn from(value: Arrows) -> Self {
+        let requires = value.requirements();
         let latex = value.to_latex_string();
         let any = Any {
             value: String::new(),
@@ -219,9 +262,10 @@ impl From<Arrows> for Element<Any> {
             list_type: None,
             items: None,
             elements: None,
+            package_options: None,
             modified: false,
         };
-        Element::new_any(any)
+        Element::new_any(any).requiring(requires)
     }
 }
 
@@ -292,6 +336,15 @@ pub enum Misc {
     Triangle,
 }
 
+/// The packages of the symbol in the symbol catalogue, if it isn't built into LaTeX
+impl Requirements for Misc {
+    fn requirements(&self) -> Vec<Package> {
+        lookup(&self.to_latex_string())
+            .map(|s| s.requirements())
+            .unwrap_or_default()
+    }
+}
+
 impl Tex for Misc {
     fn to_latex_string(&self) -> String {
         match self {
@@ -344,8 +397,18 @@ pub enum Binary {
     Cong,
 }
 
+/// The packages of the operator in the symbol catalogue, if it isn't built into LaTeX
+impl Requirements for Binary {
+    fn requirements(&self) -> Vec<Package> {
+        lookup(&self.to_latex_string())
+            .map(|s| s.requirements())
+            .unwrap_or_default()
+    }
+}
+
 impl From<Binary> for Element<Any> {
     fn from(value: Binary) -> Self {
+        let requires = value.requirements();
         let latex = value.to_latex_string();
         let any = Any {
             value: String::new(),
@@ -357,9 +420,10 @@ impl From<Binary> for Element<Any> {
             list_type: None,
             items: None,
             elements: None,
+            package_options: None,
             modified: false,
         };
-        Element::new_any(any)
+        Element::new_any(any).requiring(requires)
     }
 }
 
@@ -376,7 +440,7 @@ impl Tex for Binary {
             Binary::Geq => "\\geq".to_string(),
             Binary::In => "\\in".to_string(),
             Binary::Perp => "\\perp".to_string(),
-            Binary::Nothing => "\\nothing".to_string(),
+            Binary::Nothing => "\\varnothing".to_string(),
             Binary::Subset => "\\subset".to_string(),
             Binary::Simeq => "\\simeq".to_string(),
             Binary::Approx => "\\approx".to_string(),
@@ -384,7 +448,7 @@ impl Tex for Binary {
             Binary::Vee => "\\vee".to_string(),
             Binary::OPlus => "\\oplus".to_string(),
             Binary::OTimes => "\\otimes".to_string(),
-            Binary::Box => "\\box".to_string(),
+            Binary::Box => "\\Box".to_string(),
             Binary::BoxTimes => "\\boxtimes".to_string(),
             Binary::Equiv => "\\equiv".to_string(),
             Binary::Cong => "\\cong".to_string(),
@@ -410,7 +474,8 @@ pub fn equation_env(altered: bool) -> Environment {
 
 /// Provides the `equation` environment with a nested `split` environment with your array of equations.
 ///
-/// > Make sure these equations are `TextType::Normal` and contain a newline except for the last line
+/// > Make sure these equations are `TextType::Normal` created with `Text::raw()` and contain a newline except for the last line,
+/// > or use a `MultiLine` of kind `MultiLineKind::Split` instead, which is placed in `equation` itself
 pub fn equation_split_env(altered: bool, elements: Vec<Element<Any>>) -> Environment {
     let mut eq = equation_env(altered);
     let mut split_env = Environment::new("split");
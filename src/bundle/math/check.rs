@@ -0,0 +1,310 @@
+use super::{lookup, Func};
+use crate::Tex;
+use std::fmt::{Display, Formatter};
+
+// Commands valid in math besides the symbols and functions
+const MATH_COMMANDS: [&str; 84] = [
+    "frac",
+    "dfrac",
+    "tfrac",
+    "cfrac",
+    "binom",
+    "dbinom",
+    "tbinom",
+    "sqrt",
+    "left",
+    "right",
+    "middle",
+    "big",
+    "Big",
+    "bigg",
+    "Bigg",
+    "bigl",
+    "bigr",
+    "Bigl",
+    "Bigr",
+    "biggl",
+    "biggr",
+    "Biggl",
+    "Biggr",
+    "begin",
+    "end",
+    "text",
+    "textbf",
+    "textit",
+    "textrm",
+    "textsf",
+    "texttt",
+    "mbox",
+    "hbox",
+    "operatorname",
+    "lim",
+    "limsup",
+    "liminf",
+    "Pr",
+    "hom",
+    "bmod",
+    "pmod",
+    "mod",
+    "pod",
+    "quad",
+    "qquad",
+    "displaystyle",
+    "textstyle",
+    "scriptstyle",
+    "limits",
+    "nolimits",
+    "overset",
+    "underset",
+    "stackrel",
+    "phantom",
+    "hphantom",
+    "vphantom",
+    "boxed",
+    "not",
+    "label",
+    "ref",
+    "eqref",
+    "tag",
+    "notag",
+    "nonumber",
+    "hline",
+    "intertext",
+    "mathop",
+    "mathbin",
+    "mathrel",
+    "cdotp",
+    "colon",
+    "lbrack",
+    "rbrack",
+    "implies",
+    "impliedby",
+    "hspace",
+    "vspace",
+    "coth",
+    "substack",
+    "dotsb",
+    "dotsc",
+    "dotsi",
+    "dotsm",
+    "dotso",
+];
+
+// Commands that only work in text mode
+const TEXT_ONLY: [&str; 30] = [
+    "par",
+    "section",
+    "subsection",
+    "subsubsection",
+    "chapter",
+    "part",
+    "paragraph",
+    "subparagraph",
+    "item",
+    "noindent",
+    "indent",
+    "footnote",
+    "maketitle",
+    "newpage",
+    "clearpage",
+    "centering",
+    "raggedright",
+    "raggedleft",
+    "textbackslash",
+    "textasciitilde",
+    "textasciicircum",
+    "textbar",
+    "textless",
+    "textgreater",
+    "textbullet",
+    "textunderscore",
+    "ss",
+    "ae",
+    "oe",
+    "aa",
+];
+
+/// A problem found in latex math, at a byte offset of the checked string
+#[derive(Debug, Clone, PartialOrd, PartialEq)]
+pub enum MathIssue {
+    /// A `{` that is never closed
+    UnclosedBrace(usize),
+    /// A `}` without a matching `{`
+    UnexpectedBrace(usize),
+    /// A `\left` without a matching `\right` in the same group
+    UnmatchedLeft(usize),
+    /// A `\right` without a matching `\left` in the same group
+    UnmatchedRight(usize),
+    /// A command that isn't a known math command or symbol
+    UnknownCommand(usize, String),
+    /// A command that only works in text mode
+    TextModeCommand(usize, String),
+}
+
+impl Display for MathIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MathIssue::UnclosedBrace(i) => write!(f, "unclosed `{{` at {i}"),
+            MathIssue::UnexpectedBrace(i) => write!(f, "unexpected `}}` at {i}"),
+            MathIssue::UnmatchedLeft(i) => write!(f, r"`\left` without `\right` at {i}"),
+            MathIssue::UnmatchedRight(i) => write!(f, r"`\right` without `\left` at {i}"),
+            MathIssue::UnknownCommand(i, name) => write!(f, r"unknown command `\{name}` at {i}"),
+            MathIssue::TextModeCommand(i, name) => {
+                write!(f, r"text mode command `\{name}` in math at {i}")
+            }
+        }
+    }
+}
+
+// the groups that must be closed in order
+enum Open {
+    Brace(usize),
+    Left(usize),
+}
+
+/// Checks the braces, `\left` and `\right` pairs and the commands of latex math,
+/// like the content of `Text` with `TextType::Math`
+pub fn check_math(latex: &str) -> Vec<MathIssue> {
+    check_math_with(latex, &[])
+}
+
+/// Checks latex math like `check_math()`, also accepting the commands given
+/// without their backslash, like the ones defined with `\newcommand`
+pub fn check_math_with(latex: &str, known: &[&str]) -> Vec<MathIssue> {
+    let mut issues = Vec::new();
+    let mut open: Vec<Open> = Vec::new();
+    let bytes = latex.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                i = latex[i..].find('\n').map_or(latex.len(), |n| i + n);
+                continue;
+            }
+            b'{' => open.push(Open::Brace(i)),
+            b'}' => loop {
+                match open.pop() {
+                    Some(Open::Brace(_)) => break,
+                    Some(Open::Left(l)) => issues.push(MathIssue::UnmatchedLeft(l)),
+                    None => {
+                        issues.push(MathIssue::UnexpectedBrace(i));
+                        break;
+                    }
+                }
+            },
+            b'\\' => {
+                let start = i;
+                let name: String = latex[i + 1..]
+                    .chars()
+                    .take_while(|c| c.is_ascii_alphabetic())
+                    .collect();
+                if name.is_empty() {
+                    // a control symbol like `\{` or `\,`
+                    i += 1 + latex[i + 1..].chars().next().map_or(0, char::len_utf8);
+                    continue;
+                }
+                i += 1 + name.len();
+                match name.as_str() {
+                    "left" => {
+                        open.push(Open::Left(start));
+                        i = skip_delimiter(latex, i);
+                        continue;
+                    }
+                    "right" => {
+                        match open.last() {
+                            Some(Open::Left(_)) => {
+                                open.pop();
+                            }
+                            _ => issues.push(MathIssue::UnmatchedRight(start)),
+                        }
+                        i = skip_delimiter(latex, i);
+                        continue;
+                    }
+                    _ => {}
+                }
+                if TEXT_ONLY.contains(&name.as_str()) {
+                    issues.push(MathIssue::TextModeCommand(start, name))
+                } else if !is_known(&name, known) {
+                    issues.push(MathIssue::UnknownCommand(start, name))
+                }
+                continue;
+            }
+            _ => {}
+        }
+        i += 1
+    }
+    for group in open {
+        match group {
+            Open::Brace(i) => issues.push(MathIssue::UnclosedBrace(i)),
+            Open::Left(i) => issues.push(MathIssue::UnmatchedLeft(i)),
+        }
+    }
+    issues.sort_by_key(|issue| match issue {
+        MathIssue::UnclosedBrace(i)
+        | MathIssue::UnexpectedBrace(i)
+        | MathIssue::UnmatchedLeft(i)
+        | MathIssue::UnmatchedRight(i)
+        | MathIssue::UnknownCommand(i, _)
+        | MathIssue::TextModeCommand(i, _) => *i,
+    });
+    issues
+}
+
+// whether the command is a symbol, a function or another math command
+fn is_known(name: &str, known: &[&str]) -> bool {
+    known.contains(&name)
+        || MATH_COMMANDS.contains(&name)
+        || lookup(name).is_some()
+        || FUNCTIONS.iter().any(|f| f.to_latex_string()[1..] == *name)
+}
+
+// returns the position after the delimiter following `\left` or `\right`
+fn skip_delimiter(latex: &str, i: usize) -> usize {
+    let rest = &latex[i..];
+    let trimmed = rest.trim_start();
+    let i = i + rest.len() - trimmed.len();
+    let mut chars = trimmed.chars();
+    match chars.next() {
+        Some('\\') => {
+            let name = chars.take_while(|c| c.is_ascii_alphabetic()).count();
+            // a control symbol like `\{` is one character long
+            let len = if name == 0 {
+                trimmed[1..].chars().next().map_or(0, char::len_utf8)
+            } else {
+                name
+            };
+            i + 1 + len
+        }
+        Some(c) => i + c.len_utf8(),
+        None => i,
+    }
+}
+
+const FUNCTIONS: [Func; 26] = [
+    Func::Sin,
+    Func::Cos,
+    Func::Tan,
+    Func::Cot,
+    Func::Sec,
+    Func::Csc,
+    Func::ArcSin,
+    Func::ArcCos,
+    Func::ArcTan,
+    Func::Sinh,
+    Func::Cosh,
+    Func::Tanh,
+    Func::Exp,
+    Func::Log,
+    Func::Ln,
+    Func::Lg,
+    Func::Det,
+    Func::Dim,
+    Func::Ker,
+    Func::Gcd,
+    Func::Max,
+    Func::Min,
+    Func::Sup,
+    Func::Inf,
+    Func::Arg,
+    Func::Deg,
+];
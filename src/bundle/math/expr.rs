@@ -0,0 +1,564 @@
+use super::{Arrows, Binary, Cases, Greek, Matrix, Misc};
+use crate::{escape, Any, Element, Level, Package, Requirements, Tex, Type};
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Functions typeset upright like `\sin`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Func {
+    Sin,
+    Cos,
+    Tan,
+    Cot,
+    Sec,
+    Csc,
+    ArcSin,
+    ArcCos,
+    ArcTan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Exp,
+    Log,
+    Ln,
+    Lg,
+    Det,
+    Dim,
+    Ker,
+    Gcd,
+    Max,
+    Min,
+    Sup,
+    Inf,
+    Arg,
+    Deg,
+    /// Any other name using `\operatorname{}`, requires `amsmath`
+    Custom(String),
+}
+
+impl Tex for Func {
+    fn to_latex_string(&self) -> String {
+        let name = match self {
+            Func::Sin => "sin",
+            Func::Cos => "cos",
+            Func::Tan => "tan",
+            Func::Cot => "cot",
+            Func::Sec => "sec",
+            Func::Csc => "csc",
+            Func::ArcSin => "arcsin",
+            Func::ArcCos => "arccos",
+            Func::ArcTan => "arctan",
+            Func::Sinh => "sinh",
+            Func::Cosh => "cosh",
+            Func::Tanh => "tanh",
+            Func::Exp => "exp",
+            Func::Log => "log",
+            Func::Ln => "ln",
+            Func::Lg => "lg",
+            Func::Det => "det",
+            Func::Dim => "dim",
+            Func::Ker => "ker",
+            Func::Gcd => "gcd",
+            Func::Max => "max",
+            Func::Min => "min",
+            Func::Sup => "sup",
+            Func::Inf => "inf",
+            Func::Arg => "arg",
+            Func::Deg => "deg",
+            Func::Custom(name) => return format!(r"\operatorname{{{name}}}"),
+        };
+        format!(r"\{name}")
+    }
+}
+
+/// Delimiters sized to their content with `\left` and `\right`
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Delimiter {
+    /// `( )`
+    Paren,
+    /// `[ ]`
+    Bracket,
+    /// `\{ \}`
+    Brace,
+    /// `| |`
+    Abs,
+    /// `\| \|`
+    Norm,
+    /// `\langle \rangle`
+    Angle,
+    /// `\lfloor \rfloor`
+    Floor,
+    /// `\lceil \rceil`
+    Ceil,
+}
+
+impl Delimiter {
+    /// Returns the opening and closing delimiters
+    pub fn pair(&self) -> (&'static str, &'static str) {
+        match self {
+            Delimiter::Paren => ("(", ")"),
+            Delimiter::Bracket => ("[", "]"),
+            Delimiter::Brace => (r"\{", r"\}"),
+            Delimiter::Abs => ("|", "|"),
+            Delimiter::Norm => (r"\|", r"\|"),
+            Delimiter::Angle => (r"\langle", r"\rangle"),
+            Delimiter::Floor => (r"\lfloor", r"\rfloor"),
+            Delimiter::Ceil => (r"\lceil", r"\rceil"),
+        }
+    }
+}
+
+/// A math expression rendered without the surrounding `$`, built with the
+/// constructors below and the `+`, `-`, `*`, `/` operators, where `/` builds a fraction.
+///
+/// Parentheses are only added where the precedence of the operators requires them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Expr {
+    Int(i64),
+    Float(f64),
+    /// A variable written as is, like `x` or `x_0`
+    Var(String),
+    /// A symbol or other latex leaf with the package it requires, like `\alpha`
+    Symbol(String, Option<String>),
+    /// Latex written as is, like `x^{2}`, which is never put in parentheses
+    Raw(String),
+    /// Upright text using `\text{}`, requires `amsmath`
+    Text(String),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    /// Multiplication using `\cdot`
+    Mul(Box<Expr>, Box<Expr>),
+    Neg(Box<Expr>),
+    Frac(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, Box<Expr>),
+    Subscript(Box<Expr>, Box<Expr>),
+    /// A square root, or the root of the given degree
+    Sqrt(Box<Expr>, Option<Box<Expr>>),
+    Sum {
+        lower: Option<Box<Expr>>,
+        upper: Option<Box<Expr>>,
+        body: Box<Expr>,
+    },
+    Prod {
+        lower: Option<Box<Expr>>,
+        upper: Option<Box<Expr>>,
+        body: Box<Expr>,
+    },
+    Integral {
+        lower: Option<Box<Expr>>,
+        upper: Option<Box<Expr>>,
+        body: Box<Expr>,
+        var: Box<Expr>,
+    },
+    Limit {
+        var: Box<Expr>,
+        to: Box<Expr>,
+        body: Box<Expr>,
+    },
+    Func(Func, Box<Expr>),
+    Delim(Delimiter, Box<Expr>),
+    /// Two expressions joined by a relation or operator symbol, like `=` or `\leq`
+    Rel(Box<Expr>, String, Box<Expr>),
+    /// Expressions written next to each other, like `2x`
+    Seq(Vec<Expr>),
+    /// A command applied to an argument with the package it requires, like `\hat{x}`
+    Command(String, Box<Expr>, Option<String>),
+    Matrix(Matrix),
+    Cases(Cases),
+}
+
+impl Expr {
+    /// A variable, like `x`
+    pub fn var(name: &str) -> Self {
+        Expr::Var(name.to_string())
+    }
+    /// Upright text, like `if`
+    pub fn text(text: &str) -> Self {
+        Expr::Text(text.to_string())
+    }
+    pub fn frac(num: impl Into<Expr>, den: impl Into<Expr>) -> Self {
+        Expr::Frac(Box::new(num.into()), Box::new(den.into()))
+    }
+    pub fn sqrt(value: impl Into<Expr>) -> Self {
+        Expr::Sqrt(Box::new(value.into()), None)
+    }
+    /// The root of the given degree, like `\sqrt[3]{x}`
+    pub fn root(degree: impl Into<Expr>, value: impl Into<Expr>) -> Self {
+        Expr::Sqrt(Box::new(value.into()), Some(Box::new(degree.into())))
+    }
+    /// A sum with optional bounds, like `\sum_{i=1}^{n} x_i`
+    pub fn sum(lower: Option<Expr>, upper: Option<Expr>, body: impl Into<Expr>) -> Self {
+        Expr::Sum {
+            lower: lower.map(Box::new),
+            upper: upper.map(Box::new),
+            body: Box::new(body.into()),
+        }
+    }
+    /// A product with optional bounds, like `\prod_{i=1}^{n} x_i`
+    pub fn prod(lower: Option<Expr>, upper: Option<Expr>, body: impl Into<Expr>) -> Self {
+        Expr::Prod {
+            lower: lower.map(Box::new),
+            upper: upper.map(Box::new),
+            body: Box::new(body.into()),
+        }
+    }
+    /// An integral over `var` with optional bounds, like `\int_{0}^{1} f \, dx`
+    pub fn integral(
+        lower: Option<Expr>,
+        upper: Option<Expr>,
+        body: impl Into<Expr>,
+        var: impl Into<Expr>,
+    ) -> Self {
+        Expr::Integral {
+            lower: lower.map(Box::new),
+            upper: upper.map(Box::new),
+            body: Box::new(body.into()),
+            var: Box::new(var.into()),
+        }
+    }
+    /// A limit as `var` goes to `to`, like `\lim_{x \to 0} f`
+    pub fn limit(var: impl Into<Expr>, to: impl Into<Expr>, body: impl Into<Expr>) -> Self {
+        Expr::Limit {
+            var: Box::new(var.into()),
+            to: Box::new(to.into()),
+            body: Box::new(body.into()),
+        }
+    }
+    /// A function applied to an argument in auto-sized parentheses, like `\sin\left(x\right)`
+    pub fn func(func: Func, arg: impl Into<Expr>) -> Self {
+        Expr::Func(func, Box::new(arg.into()))
+    }
+    /// Expressions written next to each other, like `2x`
+    pub fn seq(exprs: Vec<Expr>) -> Self {
+        Expr::Seq(exprs)
+    }
+    /// Raises the expression to a power
+    pub fn pow(self, exp: impl Into<Expr>) -> Self {
+        Expr::Pow(Box::new(self), Box::new(exp.into()))
+    }
+    /// Attaches a subscript to the expression
+    pub fn subscript(self, index: impl Into<Expr>) -> Self {
+        Expr::Subscript(Box::new(self), Box::new(index.into()))
+    }
+    /// Surrounds the expression with auto-sized delimiters
+    pub fn delim(self, delimiter: Delimiter) -> Self {
+        Expr::Delim(delimiter, Box::new(self))
+    }
+    /// Surrounds the expression with auto-sized parentheses
+    pub fn paren(self) -> Self {
+        self.delim(Delimiter::Paren)
+    }
+    /// Joins the expressions with `=`
+    pub fn equals(self, rhs: impl Into<Expr>) -> Self {
+        Expr::Rel(Box::new(self), "=".to_string(), Box::new(rhs.into()))
+    }
+    /// Joins the expressions with a symbol, like `Binary::Leq`
+    pub fn rel(self, op: impl Tex, rhs: impl Into<Expr>) -> Self {
+        Expr::Rel(Box::new(self), op.to_latex_string(), Box::new(rhs.into()))
+    }
+    // how tightly the expression binds, where higher values need fewer parentheses
+    pub(crate) fn precedence(&self) -> u8 {
+        match self {
+            Expr::Rel(..) => 0,
+            Expr::Add(..) | Expr::Sub(..) | Expr::Neg(_) => 1,
+            Expr::Int(i) if *i < 0 => 1,
+            Expr::Float(f) if f.is_sign_negative() => 1,
+            Expr::Mul(..) | Expr::Seq(_) => 2,
+            Expr::Sum { .. } | Expr::Prod { .. } | Expr::Integral { .. } => 2,
+            Expr::Limit { .. } | Expr::Func(..) => 2,
+            // forms with several arguments are put in parentheses as a base
+            Expr::Frac(..) | Expr::Sqrt(_, Some(_)) | Expr::Cases(_) => 2,
+            Expr::Pow(..) | Expr::Subscript(..) => 3,
+            _ => 4,
+        }
+    }
+    // the precedence as an operand that something follows, which big operators
+    // would take into their body
+    pub(crate) fn leading_precedence(&self) -> u8 {
+        match self {
+            Expr::Sum { .. } | Expr::Prod { .. } | Expr::Integral { .. } => 1,
+            Expr::Limit { .. } => 1,
+            _ => self.precedence(),
+        }
+    }
+    // renders the expression in parentheses when it binds looser than `min`
+    fn operand(&self, min: u8) -> String {
+        if self.precedence() < min {
+            format!("({})", self.to_latex_string())
+        } else {
+            self.to_latex_string()
+        }
+    }
+    // renders an operand that something follows like `operand()`
+    fn leading_operand(&self, min: u8) -> String {
+        if self.leading_precedence() < min {
+            format!("({})", self.to_latex_string())
+        } else {
+            self.to_latex_string()
+        }
+    }
+    // renders the optional bounds of big operators
+    fn bounds(lower: &Option<Box<Expr>>, upper: &Option<Box<Expr>>) -> String {
+        let mut bounds = String::new();
+        if let Some(lower) = lower {
+            bounds.push_str(&format!("_{{{}}}", lower.to_latex_string()))
+        }
+        if let Some(upper) = upper {
+            bounds.push_str(&format!("^{{{}}}", upper.to_latex_string()))
+        }
+        bounds
+    }
+    // returns the direct children of the expression
+    pub(crate) fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Int(_)
+            | Expr::Float(_)
+            | Expr::Var(_)
+            | Expr::Symbol(..)
+            | Expr::Raw(_)
+            | Expr::Text(_) => Vec::new(),
+            Expr::Add(a, b)
+            | Expr::Sub(a, b)
+            | Expr::Mul(a, b)
+            | Expr::Frac(a, b)
+            | Expr::Pow(a, b)
+            | Expr::Subscript(a, b)
+            | Expr::Rel(a, _, b) => vec![a, b],
+            Expr::Neg(a) | Expr::Func(_, a) | Expr::Delim(_, a) => vec![a],
+            Expr::Sqrt(a, b) => [Some(a), b.as_ref()]
+                .into_iter()
+                .flatten()
+                .map(|e| &**e)
+                .collect(),
+            Expr::Sum { lower, upper, body } | Expr::Prod { lower, upper, body } => {
+                [lower.as_ref(), upper.as_ref(), Some(body)]
+                    .into_iter()
+                    .flatten()
+                    .map(|e| &**e)
+                    .collect()
+            }
+            Expr::Integral {
+                lower,
+                upper,
+                body,
+                var,
+            } => [lower.as_ref(), upper.as_ref(), Some(body), Some(var)]
+                .into_iter()
+                .flatten()
+                .map(|e| &**e)
+                .collect(),
+            Expr::Limit { var, to, body } => vec![var, to, body],
+            Expr::Seq(exprs) => exprs.iter().collect(),
+            Expr::Command(_, a, _) => vec![a],
+            Expr::Matrix(matrix) => matrix.cells.iter().flatten().collect(),
+            Expr::Cases(cases) => cases.rows.iter().flat_map(|(v, c)| [v, c]).collect(),
+        }
+    }
+}
+
+impl Tex for Expr {
+    fn to_latex_string(&self) -> String {
+        match self {
+            Expr::Int(i) => i.to_string(),
+            Expr::Float(f) => f.to_string(),
+            Expr::Var(name) => name.to_string(),
+            Expr::Symbol(latex, _) | Expr::Raw(latex) => latex.to_string(),
+            Expr::Text(text) => format!(r"\text{{{}}}", escape(text)),
+            Expr::Add(a, b) => format!("{} + {}", a.operand(1), b.operand(2)),
+            Expr::Sub(a, b) => format!("{} - {}", a.operand(1), b.operand(2)),
+            Expr::Mul(a, b) => format!(r"{} \cdot {}", a.leading_operand(2), b.operand(2)),
+            Expr::Neg(a) => format!("-{}", a.operand(2)),
+            Expr::Frac(a, b) => format!(
+                r"\frac{{{}}}{{{}}}",
+                a.to_latex_string(),
+                b.to_latex_string()
+            ),
+            Expr::Pow(a, b) => format!("{}^{{{}}}", a.operand(4), b.to_latex_string()),
+            Expr::Subscript(a, b) => format!("{}_{{{}}}", a.operand(4), b.to_latex_string()),
+            Expr::Sqrt(a, None) => format!(r"\sqrt{{{}}}", a.to_latex_string()),
+            Expr::Sqrt(a, Some(n)) => {
+                format!(r"\sqrt[{}]{{{}}}", n.to_latex_string(), a.to_latex_string())
+            }
+            Expr::Sum { lower, upper, body } => {
+                format!(r"\sum{} {}", Expr::bounds(lower, upper), body.operand(2))
+            }
+            Expr::Prod { lower, upper, body } => {
+                format!(r"\prod{} {}", Expr::bounds(lower, upper), body.operand(2))
+            }
+            Expr::Integral {
+                lower,
+                upper,
+                body,
+                var,
+            } => format!(
+                r"\int{} {} \, d{}",
+                Expr::bounds(lower, upper),
+                body.operand(2),
+                var.operand(4)
+            ),
+            Expr::Limit { var, to, body } => format!(
+                r"\lim_{{{} \to {}}} {}",
+                var.to_latex_string(),
+                to.to_latex_string(),
+                body.operand(2)
+            ),
+            Expr::Func(func, arg) => format!(
+                r"{}\left({}\right)",
+                func.to_latex_string(),
+                arg.to_latex_string()
+            ),
+            Expr::Delim(delimiter, a) => {
+                let (open, close) = delimiter.pair();
+                format!(r"\left{open} {} \right{close}", a.to_latex_string())
+            }
+            Expr::Rel(a, op, b) => format!("{} {op} {}", a.to_latex_string(), b.to_latex_string()),
+            Expr::Seq(exprs) => {
+                let last = exprs.len().saturating_sub(1);
+                let exprs: Vec<String> = exprs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| {
+                        if i < last {
+                            e.leading_operand(2)
+                        } else {
+                            e.operand(2)
+                        }
+                    })
+                    .collect();
+                exprs.join(" ")
+            }
+            Expr::Command(name, a, _) => format!(r"\{name}{{{}}}", a.to_latex_string()),
+            Expr::Matrix(matrix) => matrix.to_latex_string(),
+            Expr::Cases(cases) => cases.to_latex_string(),
+        }
+    }
+}
+
+/// Requires `amsmath` for text and custom functions and the packages of the symbols used
+impl Requirements for Expr {
+    fn requirements(&self) -> Vec<Package> {
+        let mut packages = match self {
+            Expr::Symbol(_, Some(package)) | Expr::Command(_, _, Some(package)) => {
+                vec![Package::new(package)]
+            }
+            // the packages of the cells are added as children
+            Expr::Matrix(matrix) => matrix.environment_requirements(),
+            Expr::Cases(_) => vec![Package::new("amsmath")],
+            Expr::Text(_) | Expr::Func(Func::Custom(_), _) => vec![Package::new("amsmath")],
+            _ => Vec::new(),
+        };
+        for child in self.children() {
+            packages.extend(child.requirements())
+        }
+        packages
+    }
+}
+
+impl From<Expr> for Element<Any> {
+    fn from(value: Expr) -> Self {
+        let requires = value.requirements();
+        let latex = value.to_latex_string();
+        let any = Any {
+            value: String::new(),
+            latex,
+            type_: Type::T_Bundle,
+            level: Level::Document,
+            header_level: None,
+            text_type: None,
+            list_type: None,
+            items: None,
+            elements: None,
+            package_options: None,
+            modified: false,
+        };
+        Element::new_any(any).requiring(requires)
+    }
+}
+
+impl From<i64> for Expr {
+    fn from(value: i64) -> Self {
+        Expr::Int(value)
+    }
+}
+
+impl From<i32> for Expr {
+    fn from(value: i32) -> Self {
+        Expr::Int(value as i64)
+    }
+}
+
+impl From<f64> for Expr {
+    fn from(value: f64) -> Self {
+        Expr::Float(value)
+    }
+}
+
+impl From<&str> for Expr {
+    fn from(value: &str) -> Self {
+        Expr::var(value)
+    }
+}
+
+// symbols become leaves keeping the first package they require
+macro_rules! symbol_leaf {
+    ($($symbol: ty),+) => {
+        $(
+        impl From<$symbol> for Expr {
+            fn from(value: $symbol) -> Self {
+                let package = value.requirements().first().map(|p| p.pkg.to_string());
+                Expr::Symbol(value.to_latex_string(), package)
+            }
+        }
+        )+
+    };
+}
+
+symbol_leaf!(Binary, Misc);
+
+impl From<Greek> for Expr {
+    fn from(value: Greek) -> Self {
+        Expr::Symbol(value.to_latex_string(), None)
+    }
+}
+
+impl From<Arrows> for Expr {
+    fn from(value: Arrows) -> Self {
+        Expr::Symbol(value.to_latex_string(), None)
+    }
+}
+
+// implements an operator for expressions and anything converting into one, and for
+// `i32` and `f64` on the left, leaving out `i64` so integer literals stay unambiguous
+macro_rules! operator {
+    ($($op: ident $method: ident $variant: ident),+) => {
+        $(
+        impl<T: Into<Expr>> $op<T> for Expr {
+            type Output = Expr;
+            fn $method(self, rhs: T) -> Expr {
+                Expr::$variant(Box::new(self), Box::new(rhs.into()))
+            }
+        }
+        impl $op<Expr> for i32 {
+            type Output = Expr;
+            fn $method(self, rhs: Expr) -> Expr {
+                Expr::$variant(Box::new(Expr::from(self)), Box::new(rhs))
+            }
+        }
+        impl $op<Expr> for f64 {
+            type Output = Expr;
+            fn $method(self, rhs: Expr) -> Expr {
+                Expr::$variant(Box::new(Expr::from(self)), Box::new(rhs))
+            }
+        }
+        )+
+    };
+}
+
+operator!(Add add Add, Sub sub Sub, Mul mul Mul, Div div Frac);
+
+impl Neg for Expr {
+    type Output = Expr;
+    fn neg(self) -> Expr {
+        Expr::Neg(Box::new(self))
+    }
+}
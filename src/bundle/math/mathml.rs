@@ -0,0 +1,672 @@
+use super::*;
+use crate::parse::{read_control, read_group};
+use crate::{Environment, Tex, Text, TextType};
+
+/// Renders math as presentation MathML, alongside `Tex::to_latex_string()`
+pub trait MathMl {
+    /// Returns the MathML of the content, without the surrounding `<math>`
+    fn to_mathml(&self) -> String;
+    /// Returns a `<math>` element placed within text, like `$...$`
+    fn to_mathml_inline(&self) -> String {
+        format!(
+            r#"<math xmlns="http://www.w3.org/1998/Math/MathML">{}</math>"#,
+            self.to_mathml()
+        )
+    }
+    /// Returns a `<math>` element displayed on its own line, like the `equation` environment
+    fn to_mathml_block(&self) -> String {
+        format!(
+            r#"<math xmlns="http://www.w3.org/1998/Math/MathML" display="block">{}</math>"#,
+            self.to_mathml()
+        )
+    }
+}
+
+// escapes the characters xml gives a meaning to
+pub(crate) fn xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn mo(op: &str) -> String {
+    format!("<mo>{}</mo>", xml(op))
+}
+
+fn mrow(content: &str) -> String {
+    format!("<mrow>{content}</mrow>")
+}
+
+// a token written in latex: numbers, single characters and symbols of the catalogue,
+// falling back to the latex as text
+fn token(latex: &str) -> String {
+    let latex = latex.trim();
+    if latex.is_empty() {
+        return mrow("");
+    }
+    if let Some(n) = latex.strip_prefix('-').filter(|n| n.parse::<f64>().is_ok()) {
+        return mrow(&format!("{}<mn>{n}</mn>", mo("−")));
+    }
+    if latex.parse::<f64>().is_ok() {
+        return format!("<mn>{latex}</mn>");
+    }
+    if latex.chars().all(|c| c.is_alphabetic()) {
+        return format!("<mi>{latex}</mi>");
+    }
+    if latex.chars().count() == 1 {
+        return mo(latex);
+    }
+    match lookup(latex).filter(|_| latex.starts_with('\\')) {
+        Some(symbol) => symbol.to_mathml(),
+        None => format!("<mtext>{}</mtext>", xml(latex)),
+    }
+}
+
+impl MathMl for MathSymbol {
+    fn to_mathml(&self) -> String {
+        let c = match self.unicode {
+            Some(c) if !self.takes_argument() => c.to_string(),
+            _ => return format!("<mtext>{}</mtext>", xml(&self.to_latex_string())),
+        };
+        match self.category {
+            Category::Greek | Category::Misc => format!("<mi>{c}</mi>"),
+            Category::BigOperator => format!(r#"<mo largeop="true">{c}</mo>"#),
+            _ => mo(&c),
+        }
+    }
+}
+
+// symbols of the enums are rendered through the catalogue
+macro_rules! mathml_symbol {
+    ($($symbol: ty),+) => {
+        $(
+        impl MathMl for $symbol {
+            fn to_mathml(&self) -> String {
+                token(&self.to_latex_string())
+            }
+        }
+        )+
+    };
+}
+
+mathml_symbol!(Greek, Arrows, Binary, Misc);
+
+impl Delimiter {
+    /// Returns the opening and closing characters
+    pub fn unicode_pair(&self) -> (&'static str, &'static str) {
+        match self {
+            Delimiter::Paren => ("(", ")"),
+            Delimiter::Bracket => ("[", "]"),
+            Delimiter::Brace => ("{", "}"),
+            Delimiter::Abs => ("|", "|"),
+            Delimiter::Norm => ("‖", "‖"),
+            Delimiter::Angle => ("⟨", "⟩"),
+            Delimiter::Floor => ("⌊", "⌋"),
+            Delimiter::Ceil => ("⌈", "⌉"),
+        }
+    }
+}
+
+// wraps the content within delimiters
+fn fenced(open: &str, content: &str, close: &str) -> String {
+    mrow(&format!("{}{content}{}", mo(open), mo(close)))
+}
+
+impl Expr {
+    // renders the expression in parentheses when it binds looser than `min`
+    fn mathml_operand(&self, min: u8) -> String {
+        if self.precedence() < min {
+            fenced("(", &self.to_mathml(), ")")
+        } else {
+            self.to_mathml()
+        }
+    }
+    // renders an operand that something follows like `mathml_operand()`
+    fn mathml_leading_operand(&self, min: u8) -> String {
+        if self.leading_precedence() < min {
+            fenced("(", &self.to_mathml(), ")")
+        } else {
+            self.to_mathml()
+        }
+    }
+    // renders a big operator with its optional bounds
+    fn mathml_bounds(op: &str, lower: &Option<Box<Expr>>, upper: &Option<Box<Expr>>) -> String {
+        let op = format!(r#"<mo largeop="true">{op}</mo>"#);
+        match (lower, upper) {
+            (Some(l), Some(u)) => format!(
+                "<munderover>{op}{}{}</munderover>",
+                l.to_mathml(),
+                u.to_mathml()
+            ),
+            (Some(l), None) => format!("<munder>{op}{}</munder>", l.to_mathml()),
+            (None, Some(u)) => format!("<mover>{op}{}</mover>", u.to_mathml()),
+            (None, None) => op,
+        }
+    }
+}
+
+impl MathMl for Expr {
+    fn to_mathml(&self) -> String {
+        match self {
+            Expr::Int(_) | Expr::Float(_) => token(&self.to_latex_string()),
+            Expr::Var(name) => format!("<mi>{}</mi>", xml(name)),
+            Expr::Symbol(latex, _) => token(latex),
+            Expr::Raw(latex) => parse(latex).to_mathml(),
+            Expr::Text(text) => format!("<mtext>{}</mtext>", xml(text)),
+            Expr::Add(a, b) => mrow(&format!(
+                "{}{}{}",
+                a.mathml_operand(1),
+                mo("+"),
+                b.mathml_operand(2)
+            )),
+            Expr::Sub(a, b) => mrow(&format!(
+                "{}{}{}",
+                a.mathml_operand(1),
+                mo("−"),
+                b.mathml_operand(2)
+            )),
+            Expr::Mul(a, b) => mrow(&format!(
+                "{}{}{}",
+                a.mathml_leading_operand(2),
+                mo("⋅"),
+                b.mathml_operand(2)
+            )),
+            Expr::Neg(a) => mrow(&format!("{}{}", mo("−"), a.mathml_operand(2))),
+            Expr::Frac(a, b) => format!("<mfrac>{}{}</mfrac>", a.to_mathml(), b.to_mathml()),
+            Expr::Pow(a, b) => format!("<msup>{}{}</msup>", a.mathml_operand(4), b.to_mathml()),
+            Expr::Subscript(a, b) => {
+                format!("<msub>{}{}</msub>", a.mathml_operand(4), b.to_mathml())
+            }
+            Expr::Sqrt(a, None) => format!("<msqrt>{}</msqrt>", a.to_mathml()),
+            Expr::Sqrt(a, Some(n)) => format!("<mroot>{}{}</mroot>", a.to_mathml(), n.to_mathml()),
+            Expr::Sum { lower, upper, body } => mrow(&format!(
+                "{}{}",
+                Expr::mathml_bounds("∑", lower, upper),
+                body.mathml_operand(2)
+            )),
+            Expr::Prod { lower, upper, body } => mrow(&format!(
+                "{}{}",
+                Expr::mathml_bounds("∏", lower, upper),
+                body.mathml_operand(2)
+            )),
+            Expr::Integral {
+                lower,
+                upper,
+                body,
+                var,
+            } => {
+                let op = r#"<mo largeop="true">∫</mo>"#;
+                let op = match (lower, upper) {
+                    (Some(l), Some(u)) => {
+                        format!("<msubsup>{op}{}{}</msubsup>", l.to_mathml(), u.to_mathml())
+                    }
+                    (Some(l), None) => format!("<msub>{op}{}</msub>", l.to_mathml()),
+                    (None, Some(u)) => format!("<msup>{op}{}</msup>", u.to_mathml()),
+                    (None, None) => op.to_string(),
+                };
+                mrow(&format!(
+                    r#"{op}{}<mspace width="0.167em"/><mi>d</mi>{}"#,
+                    body.mathml_operand(2),
+                    var.mathml_operand(4)
+                ))
+            }
+            Expr::Limit { var, to, body } => {
+                let under = mrow(&format!("{}{}{}", var.to_mathml(), mo("→"), to.to_mathml()));
+                mrow(&format!(
+                    "<munder><mo>lim</mo>{under}</munder>{}",
+                    body.mathml_operand(2)
+                ))
+            }
+            Expr::Func(func, arg) => {
+                let name = match func {
+                    Func::Custom(name) => name.to_string(),
+                    _ => func.to_latex_string().trim_start_matches('\\').to_string(),
+                };
+                mrow(&format!(
+                    "<mi>{}</mi><mo>&#x2061;</mo>{}",
+                    xml(&name),
+                    fenced("(", &arg.to_mathml(), ")")
+                ))
+            }
+            Expr::Delim(delimiter, a) => {
+                let (open, close) = delimiter.unicode_pair();
+                fenced(open, &a.to_mathml(), close)
+            }
+            Expr::Rel(a, op, b) => {
+                mrow(&format!("{}{}{}", a.to_mathml(), token(op), b.to_mathml()))
+            }
+            Expr::Seq(exprs) => {
+                let last = exprs.len().saturating_sub(1);
+                let exprs: Vec<String> = exprs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| {
+                        if i < last {
+                            e.mathml_leading_operand(2)
+                        } else {
+                            e.mathml_operand(2)
+                        }
+                    })
+                    .collect();
+                mrow(&exprs.concat())
+            }
+            Expr::Command(name, a, _) => command_mathml(name, &a.to_mathml()),
+            Expr::Matrix(matrix) => matrix.to_mathml(),
+            Expr::Cases(cases) => cases.to_mathml(),
+        }
+    }
+}
+
+// renders an accent over or under its argument, or a math alphabet
+pub(crate) fn command_mathml(name: &str, arg: &str) -> String {
+    let variant = match name {
+        "mathbb" => Some("double-struck"),
+        "mathcal" => Some("script"),
+        "mathscr" => Some("script"),
+        "mathfrak" => Some("fraktur"),
+        "mathbf" | "boldsymbol" => Some("bold"),
+        "mathit" => Some("italic"),
+        "mathrm" => Some("normal"),
+        "mathsf" => Some("sans-serif"),
+        "mathtt" => Some("monospace"),
+        _ => None,
+    };
+    if let Some(variant) = variant {
+        return format!(r#"<mstyle mathvariant="{variant}">{arg}</mstyle>"#);
+    }
+    let (accent, under) = match name {
+        "hat" | "widehat" => ("^", false),
+        "check" => ("ˇ", false),
+        "tilde" | "widetilde" => ("~", false),
+        "acute" => ("´", false),
+        "grave" => ("`", false),
+        "dot" => ("˙", false),
+        "ddot" => ("¨", false),
+        "breve" => ("˘", false),
+        "bar" | "overline" => ("¯", false),
+        "vec" | "overrightarrow" => ("→", false),
+        "overleftarrow" => ("←", false),
+        "overbrace" => ("⏞", false),
+        "underline" => ("_", true),
+        "underbrace" => ("⏟", true),
+        _ => return mrow(&format!("<mtext>\\{}</mtext>{arg}", xml(name))),
+    };
+    if under {
+        format!(r#"<munder accentunder="true">{arg}{}</munder>"#, mo(accent))
+    } else {
+        format!(r#"<mover accent="true">{arg}{}</mover>"#, mo(accent))
+    }
+}
+
+// renders rows of cells as a table with the given column alignment
+fn table(rows: Vec<(Option<String>, Vec<String>)>, columnalign: &str) -> String {
+    let rows: Vec<String> = rows
+        .into_iter()
+        .map(|(id, cells)| {
+            let cells: Vec<String> = cells.iter().map(|c| format!("<mtd>{c}</mtd>")).collect();
+            match id {
+                Some(id) => format!(r#"<mtr id="{}">{}</mtr>"#, xml(&id), cells.concat()),
+                None => format!("<mtr>{}</mtr>", cells.concat()),
+            }
+        })
+        .collect();
+    if columnalign.is_empty() {
+        format!("<mtable>{}</mtable>", rows.concat())
+    } else {
+        format!(
+            r#"<mtable columnalign="{columnalign}">{}</mtable>"#,
+            rows.concat()
+        )
+    }
+}
+
+impl MathMl for Matrix {
+    /// Shortened matrices are rendered in full
+    fn to_mathml(&self) -> String {
+        let rows = self
+            .cells
+            .iter()
+            .map(|row| (None, row.iter().map(|c| c.to_mathml()).collect()))
+            .collect();
+        let columnalign = match &self.kind {
+            MatrixKind::Array(aligns) => {
+                let aligns: Vec<&str> = aligns
+                    .iter()
+                    .map(|a| match a {
+                        ColumnAlign::Left => "left",
+                        ColumnAlign::Center => "center",
+                        ColumnAlign::Right => "right",
+                    })
+                    .collect();
+                aligns.join(" ")
+            }
+            _ => String::new(),
+        };
+        let table = table(rows, &columnalign);
+        let (open, close) = match self.kind {
+            MatrixKind::Paren => ("(", ")"),
+            MatrixKind::Bracket => ("[", "]"),
+            MatrixKind::Brace => ("{", "}"),
+            MatrixKind::Vert => ("|", "|"),
+            MatrixKind::DoubleVert => ("‖", "‖"),
+            MatrixKind::Plain | MatrixKind::Array(_) => return table,
+        };
+        fenced(open, &table, close)
+    }
+}
+
+impl MathMl for Cases {
+    fn to_mathml(&self) -> String {
+        let rows = self
+            .rows
+            .iter()
+            .map(|(value, condition)| (None, vec![value.to_mathml(), condition.to_mathml()]))
+            .collect();
+        mrow(&format!("{}{}", mo("{"), table(rows, "left left")))
+    }
+}
+
+impl MathMl for MultiLine {
+    /// Labelled lines get the label's key as their `id`
+    fn to_mathml(&self) -> String {
+        let rows = self
+            .lines
+            .iter()
+            .map(|line| {
+                let id = line.label.as_ref().map(|l| l.key());
+                (id, line.cells.iter().map(|c| c.to_mathml()).collect())
+            })
+            .collect();
+        let columnalign = match self.kind {
+            MultiLineKind::Gather | MultiLineKind::Multline => "center",
+            _ => "right left",
+        };
+        table(rows, columnalign)
+    }
+}
+
+/// Math text is rendered from its latex, other text as `<mtext>`
+impl MathMl for Text {
+    fn to_mathml(&self) -> String {
+        match self.type_ {
+            TextType::Math => parse(&self.content).to_mathml(),
+            _ => format!("<mtext>{}</mtext>", xml(&self.content)),
+        }
+    }
+}
+
+/// Renders math environments like `equation_env()` and `equation_split_env()` from their latex,
+/// where the lines of aligned environments become the rows of a table
+impl MathMl for Environment {
+    fn to_mathml(&self) -> String {
+        parse(&self.to_latex_string()).to_mathml()
+    }
+}
+
+// parses latex math into expressions, as far as MathML needs them
+fn parse(latex: &str) -> Expr {
+    let mut parser = Parser { s: latex, i: 0 };
+    let mut items = Vec::new();
+    while let Some(c) = parser.peek() {
+        if c == '^' || c == '_' {
+            parser.i += 1;
+            let base = Box::new(items.pop().unwrap_or(Expr::Seq(Vec::new())));
+            let script = Box::new(parser.argument());
+            items.push(match c {
+                '^' => Expr::Pow(base, script),
+                _ => Expr::Subscript(base, script),
+            });
+        } else {
+            items.extend(parser.atom())
+        }
+    }
+    match items.len() {
+        1 => items.remove(0),
+        _ => Expr::Seq(items),
+    }
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    i: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.s[self.i..].chars().next()
+    }
+    fn skip_spaces(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.i += c.len_utf8()
+        }
+    }
+    // the content of a group delimited by `open` and `close` at the position
+    fn group(&mut self, open: u8, close: u8) -> Option<&'a str> {
+        self.skip_spaces();
+        let (group, end) = read_group(self.s, self.i, open, close)?;
+        self.i = end;
+        Some(group)
+    }
+    // the latex of the next argument, a group or a single character or command
+    fn raw_argument(&mut self) -> &'a str {
+        if let Some(group) = self.group(b'{', b'}') {
+            return group;
+        }
+        let start = self.i;
+        match self.peek() {
+            Some('\\') => self.i = read_control(self.s, self.i).1,
+            Some(c) => self.i += c.len_utf8(),
+            None => {}
+        }
+        &self.s[start..self.i]
+    }
+    fn argument(&mut self) -> Expr {
+        parse(self.raw_argument())
+    }
+    // the next atom, or nothing for spaces and latex without output like `\label{}`
+    fn atom(&mut self) -> Option<Expr> {
+        let c = self.peek()?;
+        match c {
+            '{' => {
+                if let Some(group) = self.group(b'{', b'}') {
+                    return Some(parse(group));
+                }
+                // an unclosed brace is left out
+                self.i += 1
+            }
+            '\\' => return self.command(),
+            _ => self.i += c.len_utf8(),
+        }
+        let expr = match c {
+            _ if c.is_whitespace() => return None,
+            '{' | '}' | '&' | '~' => return None,
+            '0'..='9' | '.' => {
+                let start = self.i - 1;
+                let digits = self.s[self.i..].find(|d: char| !d.is_ascii_digit() && d != '.');
+                self.i = digits.map_or(self.s.len(), |d| self.i + d);
+                Expr::Symbol(self.s[start..self.i].to_string(), None)
+            }
+            '-' => Expr::Symbol("−".to_string(), None),
+            '*' => Expr::Symbol("∗".to_string(), None),
+            '\'' => Expr::Symbol("′".to_string(), None),
+            _ if c.is_alphabetic() => Expr::Var(c.to_string()),
+            _ => Expr::Symbol(c.to_string(), None),
+        };
+        Some(expr)
+    }
+    fn command(&mut self) -> Option<Expr> {
+        let (name, end) = read_control(self.s, self.i);
+        self.i = end;
+        let expr = match name {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                Expr::Frac(Box::new(self.argument()), Box::new(self.argument()))
+            }
+            "sqrt" => {
+                let degree = self.group(b'[', b']').map(|d| Box::new(parse(d)));
+                Expr::Sqrt(Box::new(self.argument()), degree)
+            }
+            "text" | "textrm" | "textit" | "textbf" | "mbox" => {
+                Expr::Text(self.raw_argument().to_string())
+            }
+            "operatorname" | "mathrm" => Expr::Var(self.raw_argument().to_string()),
+            "begin" => return Some(self.environment()),
+            "end" | "label" | "tag" | "tag*" => {
+                self.raw_argument();
+                return None;
+            }
+            // the delimiter that follows is an atom of its own
+            "left" | "right" | "middle" | "big" | "Big" | "bigg" | "Bigg" | "bigl" | "bigr"
+            | "Bigl" | "Bigr" | "biggl" | "biggr" | "Biggl" | "Biggr" => {
+                self.skip_spaces();
+                if self.peek() == Some('.') {
+                    self.i += 1
+                }
+                return None;
+            }
+            "{" | "}" | "%" | "&" | "#" | "$" | "_" => Expr::Symbol(name.to_string(), None),
+            "|" => Expr::Symbol("‖".to_string(), None),
+            _ => match lookup(name) {
+                Some(symbol) if symbol.takes_argument() => {
+                    Expr::Command(name.to_string(), Box::new(self.argument()), None)
+                }
+                Some(symbol) => Expr::Symbol(symbol.to_latex_string(), None),
+                // functions like `\sin` and macros the catalogue doesn't know
+                None if name.starts_with(|c: char| c.is_ascii_alphabetic()) => match name {
+                    "nonumber" | "notag" | "displaystyle" | "textstyle" | "limits" | "nolimits"
+                    | "quad" | "qquad" => return None,
+                    _ => Expr::Var(name.to_string()),
+                },
+                // spacing and line breaks
+                None => return None,
+            },
+        };
+        Some(expr)
+    }
+    // an environment after its `\begin`, where matrices and aligned lines become tables
+    fn environment(&mut self) -> Expr {
+        let name = self.raw_argument().trim();
+        let (begin, end) = (format!(r"\begin{{{name}}}"), format!(r"\end{{{name}}}"));
+        let start = self.i;
+        let mut depth = 1;
+        let body_end = loop {
+            let rest = &self.s[self.i..];
+            match (rest.find(&begin), rest.find(&end)) {
+                (Some(b), Some(e)) if b < e => {
+                    depth += 1;
+                    self.i += b + begin.len()
+                }
+                (_, Some(e)) => {
+                    depth -= 1;
+                    self.i += e + end.len();
+                    if depth == 0 {
+                        break self.i - end.len();
+                    }
+                }
+                (_, None) => {
+                    self.i = self.s.len();
+                    break self.i;
+                }
+            }
+        };
+        let mut body = Parser {
+            s: &self.s[start..body_end],
+            i: 0,
+        };
+        let kind = match name.trim_end_matches('*') {
+            "matrix" | "smallmatrix" => MatrixKind::Plain,
+            "pmatrix" => MatrixKind::Paren,
+            "bmatrix" => MatrixKind::Bracket,
+            "Bmatrix" => MatrixKind::Brace,
+            "vmatrix" => MatrixKind::Vert,
+            "Vmatrix" => MatrixKind::DoubleVert,
+            "array" => {
+                let spec = body.raw_argument().chars();
+                let aligns = spec.filter_map(|c| match c {
+                    'l' => Some(ColumnAlign::Left),
+                    'c' => Some(ColumnAlign::Center),
+                    'r' => Some(ColumnAlign::Right),
+                    _ => None,
+                });
+                MatrixKind::Array(aligns.collect())
+            }
+            "gather" | "gathered" | "multline" => MatrixKind::Array(vec![ColumnAlign::Center]),
+            "align" | "aligned" | "split" | "flalign" | "alignat" | "alignedat" => {
+                if name.starts_with("alignat") || name == "alignedat" {
+                    body.raw_argument();
+                }
+                // filled in once the number of columns is known
+                MatrixKind::Array(Vec::new())
+            }
+            "cases" | "dcases" => {
+                let rows = rows(&body.s[body.i..]).into_iter().map(|mut cells| {
+                    cells.resize(2, Expr::Seq(Vec::new()));
+                    let condition = cells.pop().unwrap_or(Expr::Seq(Vec::new()));
+                    (cells.remove(0), condition)
+                });
+                return Expr::Cases(Cases {
+                    rows: rows.collect(),
+                });
+            }
+            _ => return parse(&body.s[body.i..]),
+        };
+        let cells = rows(&body.s[body.i..]);
+        let kind = match kind {
+            // aligned lines alternate between right and left aligned columns
+            MatrixKind::Array(aligns) if aligns.is_empty() => {
+                let cols = cells.iter().map(|r| r.len()).max().unwrap_or(0);
+                let aligns = (0..cols).map(|c| match c % 2 {
+                    0 => ColumnAlign::Right,
+                    _ => ColumnAlign::Left,
+                });
+                MatrixKind::Array(aligns.collect())
+            }
+            kind => kind,
+        };
+        Expr::Matrix(Matrix::from_exprs(kind, cells))
+    }
+}
+
+// splits the body of an environment into rows at `\\\\` and cells at `&`,
+// leaving out an empty last row
+fn rows(body: &str) -> Vec<Vec<Expr>> {
+    let mut rows: Vec<Vec<Expr>> = split(body, "\\\\")
+        .into_iter()
+        .map(|row| split(row, "&").into_iter().map(parse).collect())
+        .collect();
+    if rows
+        .last()
+        .is_some_and(|r| r.len() == 1 && r[0] == Expr::Seq(Vec::new()))
+    {
+        rows.pop();
+    }
+    rows
+}
+
+// splits latex at the separator, outside of groups and nested environments
+fn split<'a>(latex: &'a str, separator: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start, mut i) = (0usize, 0, 0);
+    while i < latex.len() {
+        let rest = &latex[i..];
+        if depth == 0 && rest.starts_with(separator) {
+            parts.push(&latex[start..i]);
+            i += separator.len();
+            start = i;
+            continue;
+        }
+        if rest.starts_with(r"\begin{") || rest.starts_with('{') {
+            depth += 1
+        } else if rest.starts_with(r"\end{") || rest.starts_with('}') {
+            depth = depth.saturating_sub(1)
+        }
+        // control symbols like `\&` are skipped as a whole
+        let c = rest.chars().next().map_or(1, char::len_utf8);
+        i += match rest.strip_prefix('\\').and_then(|r| r.chars().next()) {
+            Some(next) if !next.is_ascii_alphabetic() => 1 + next.len_utf8(),
+            _ => c,
+        };
+    }
+    parts.push(&latex[start..]);
+    parts
+}
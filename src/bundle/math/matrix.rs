@@ -0,0 +1,264 @@
+use super::Expr;
+use crate::{Any, Element, Level, Package, Requirements, Tex, Type};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// The alignment of a column within `array`
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ColumnAlign {
+    Left,
+    Center,
+    Right,
+}
+
+impl Tex for ColumnAlign {
+    fn to_latex_string(&self) -> String {
+        match self {
+            ColumnAlign::Left => "l",
+            ColumnAlign::Center => "c",
+            ColumnAlign::Right => "r",
+        }
+        .to_string()
+    }
+}
+
+/// The environments a matrix is rendered with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MatrixKind {
+    /// `matrix`, without delimiters
+    Plain,
+    /// `pmatrix`, with parentheses
+    Paren,
+    /// `bmatrix`, with brackets
+    Bracket,
+    /// `Bmatrix`, with braces
+    Brace,
+    /// `vmatrix`, with vertical bars
+    Vert,
+    /// `Vmatrix`, with double vertical bars
+    DoubleVert,
+    /// `array` with the alignment of each column, where missing ones are centered
+    Array(Vec<ColumnAlign>),
+}
+
+impl MatrixKind {
+    // the `amsmath` environment and the delimiters used when rendered as an array
+    fn parts(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            MatrixKind::Plain => ("matrix", "", ""),
+            MatrixKind::Paren => ("pmatrix", "(", ")"),
+            MatrixKind::Bracket => ("bmatrix", "[", "]"),
+            MatrixKind::Brace => ("Bmatrix", r"\{", r"\}"),
+            MatrixKind::Vert => ("vmatrix", "|", "|"),
+            MatrixKind::DoubleVert => ("Vmatrix", r"\|", r"\|"),
+            MatrixKind::Array(_) => ("array", "", ""),
+        }
+    }
+}
+
+/// A matrix built from row-major data.
+///
+/// Large matrices can be shortened with `\cdots`, `\vdots` and `\ddots`,
+/// keeping the first and last rows and columns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Matrix {
+    pub kind: MatrixKind,
+    pub cells: Vec<Vec<Expr>>,
+    /// The number of columns left of a vertical rule, as in augmented matrices
+    pub augment: Option<usize>,
+    /// The maximum number of rows shown, including the ellipsis row
+    pub max_rows: Option<usize>,
+    /// The maximum number of columns shown, including the ellipsis column
+    pub max_cols: Option<usize>,
+}
+
+impl Matrix {
+    /// A matrix from rows of values implementing `Display`
+    pub fn new<T: Display>(
+        kind: MatrixKind,
+        rows: impl IntoIterator<Item = impl IntoIterator<Item = T>>,
+    ) -> Self {
+        Self::formatted(kind, rows, |v| v.to_string())
+    }
+    /// A matrix from rows of values implementing `Tex`, kept as their latex
+    pub fn from_tex<T: Tex>(
+        kind: MatrixKind,
+        rows: impl IntoIterator<Item = impl IntoIterator<Item = T>>,
+    ) -> Self {
+        Self::formatted(kind, rows, |v| v.to_latex_string())
+    }
+    /// A matrix from rows of expressions, which keep their structure in MathML
+    pub fn from_exprs(
+        kind: MatrixKind,
+        rows: impl IntoIterator<Item = impl IntoIterator<Item = impl Into<Expr>>>,
+    ) -> Self {
+        let cells = rows
+            .into_iter()
+            .map(|row| row.into_iter().map(Into::into).collect())
+            .collect();
+        Self::with_cells(kind, cells)
+    }
+    /// A matrix from rows of numbers with a fixed number of decimal places
+    pub fn with_precision(
+        kind: MatrixKind,
+        rows: impl IntoIterator<Item = impl IntoIterator<Item = f64>>,
+        precision: usize,
+    ) -> Self {
+        Self::formatted(kind, rows, |v| format!("{v:.precision$}"))
+    }
+    /// A matrix from rows of values rendered by `format`
+    pub fn formatted<T>(
+        kind: MatrixKind,
+        rows: impl IntoIterator<Item = impl IntoIterator<Item = T>>,
+        format: impl Fn(&T) -> String,
+    ) -> Self {
+        let cells = rows
+            .into_iter()
+            .map(|row| row.into_iter().map(|v| Expr::Raw(format(&v))).collect())
+            .collect();
+        Self::with_cells(kind, cells)
+    }
+    fn with_cells(kind: MatrixKind, cells: Vec<Vec<Expr>>) -> Self {
+        Self {
+            kind,
+            cells,
+            augment: None,
+            max_rows: None,
+            max_cols: None,
+        }
+    }
+    /// Places a vertical rule after the first `columns` columns
+    pub fn augment(mut self, columns: usize) -> Self {
+        self.augment = Some(columns);
+        self
+    }
+    /// Shortens the matrix to at most `rows` rows and `cols` columns using ellipses,
+    /// where each limit is at least 3
+    pub fn truncate(mut self, rows: usize, cols: usize) -> Self {
+        self.max_rows = Some(rows);
+        self.max_cols = Some(cols);
+        self
+    }
+    // the packages of the environment, without the ones of the cells
+    pub(crate) fn environment_requirements(&self) -> Vec<Package> {
+        if self.augment.is_some() || matches!(self.kind, MatrixKind::Array(_)) {
+            Vec::new()
+        } else {
+            vec![Package::new("amsmath")]
+        }
+    }
+    /// The number of rows and of columns of the data
+    pub fn dimensions(&self) -> (usize, usize) {
+        let cols = self.cells.iter().map(|r| r.len()).max().unwrap_or(0);
+        (self.cells.len(), cols)
+    }
+}
+
+// the indices shown out of `len`, with `None` standing for the ellipsis,
+// keeping at least one index on each side of it
+fn shown(len: usize, max: Option<usize>) -> Vec<Option<usize>> {
+    match max {
+        Some(max) if len > max.max(3) => {
+            let max = max.max(3);
+            let mut indices: Vec<Option<usize>> = (0..max - 2).map(Some).collect();
+            indices.push(None);
+            indices.push(Some(len - 1));
+            indices
+        }
+        _ => (0..len).map(Some).collect(),
+    }
+}
+
+impl Tex for Matrix {
+    fn to_latex_string(&self) -> String {
+        let (rows, cols) = self.dimensions();
+        let rows = shown(rows, self.max_rows);
+        let cols = shown(cols, self.max_cols);
+        let lines: Vec<String> = rows
+            .iter()
+            .map(|r| {
+                let cells: Vec<String> = cols
+                    .iter()
+                    .map(|c| match (r, c) {
+                        (Some(r), Some(c)) => self.cells[*r]
+                            .get(*c)
+                            .map_or(String::new(), |e| e.to_latex_string()),
+                        (None, Some(_)) => r"\vdots".to_string(),
+                        (Some(_), None) => r"\cdots".to_string(),
+                        (None, None) => r"\ddots".to_string(),
+                    })
+                    .collect();
+                cells.join(" & ")
+            })
+            .collect();
+        let body = lines.join(" \\\\\n");
+        let (env, open, close) = self.kind.parts();
+        if self.augment.is_none() && !matches!(self.kind, MatrixKind::Array(_)) {
+            return format!("\\begin{{{env}}}\n{body}\n\\end{{{env}}}");
+        }
+        let aligns = match &self.kind {
+            MatrixKind::Array(aligns) => aligns.clone(),
+            _ => Vec::new(),
+        };
+        // the ellipsis column sorts where the hidden columns start
+        let hidden = cols.iter().position(|c| c.is_none());
+        let mut spec = String::new();
+        let mut ruled = false;
+        for (i, c) in cols.iter().enumerate() {
+            let original = c.or(hidden).unwrap_or(i);
+            if !ruled && i > 0 && self.augment.is_some_and(|a| original >= a) {
+                spec.push('|');
+                ruled = true
+            }
+            let align = c
+                .and_then(|c| aligns.get(c))
+                .unwrap_or(&ColumnAlign::Center);
+            spec.push_str(&align.to_latex_string());
+        }
+        let array = format!("\\begin{{array}}{{{spec}}}\n{body}\n\\end{{array}}");
+        if open.is_empty() {
+            array
+        } else {
+            format!(r"\left{open}{array}\right{close}")
+        }
+    }
+}
+
+/// Requires `amsmath` unless rendered as an `array`, and the packages of the cells
+impl Requirements for Matrix {
+    fn requirements(&self) -> Vec<Package> {
+        let mut packages = self.environment_requirements();
+        for cell in self.cells.iter().flatten() {
+            packages.extend(cell.requirements())
+        }
+        packages
+    }
+}
+
+impl From<Matrix> for Element<Any> {
+    fn from(value: Matrix) -> Self {
+        let requires = value.requirements();
+        let latex = value.to_latex_string();
+        let any = Any {
+            value: String::new(),
+            latex,
+            type_: Type::T_Bundle,
+            level: Level::Document,
+            header_level: None,
+            text_type: None,
+            list_type: None,
+            items: None,
+            elements: None,
+            package_options: None,
+            modified: false,
+        };
+        Element::new_any(any).requiring(requires)
+    }
+}
+
+/// Lets a matrix be part of an expression, like `A = matrix`
+impl From<Matrix> for Expr {
+    fn from(value: Matrix) -> Self {
+        Expr::Matrix(value)
+    }
+}
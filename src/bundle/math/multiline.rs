@@ -0,0 +1,279 @@
+use super::Expr;
+use crate::{Any, Element, Error, Label, Level, Package, Requirements, Tex, Type};
+use serde::{Deserialize, Serialize};
+
+/// The `amsmath` environments holding several lines of math
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MultiLineKind {
+    /// Lines aligned at every `&`
+    Align,
+    /// Centered lines without alignment
+    Gather,
+    /// A single long equation, the first line on the left and the last on the right
+    Multline,
+    /// Lines aligned at the given number of column pairs without added space
+    AlignAt(u8),
+    /// Like `Align` using the full width of the line
+    FlAlign,
+    /// A single equation over several lines, placed within `equation`,
+    /// or `equation*` when starred, as it only works in display math
+    Split,
+}
+
+impl MultiLineKind {
+    /// Returns the name of the environment, where `starred` drops the numbering
+    pub fn name(&self, starred: bool) -> String {
+        let name = match self {
+            MultiLineKind::Align => "align",
+            MultiLineKind::Gather => "gather",
+            MultiLineKind::Multline => "multline",
+            MultiLineKind::AlignAt(_) => "alignat",
+            MultiLineKind::FlAlign => "flalign",
+            MultiLineKind::Split => return "split".to_string(),
+        };
+        if starred {
+            format!("{name}*")
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+/// A line of a multi-line environment, where cells are separated by alignment points
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MathLine {
+    pub cells: Vec<Expr>,
+    pub label: Option<Label>,
+    pub nonumber: bool,
+}
+
+impl MathLine {
+    /// A line from latex cells, like `vec!["x", "= 1"]` for `x &= 1`
+    pub fn new(cells: Vec<&str>) -> Self {
+        Self {
+            cells: cells.iter().map(|c| Expr::Raw(c.to_string())).collect(),
+            label: None,
+            nonumber: false,
+        }
+    }
+    /// A line from expressions, one per cell
+    pub fn from_exprs(cells: Vec<Expr>) -> Self {
+        Self {
+            cells,
+            label: None,
+            nonumber: false,
+        }
+    }
+    /// A line aligned at a relation, like `lhs &= rhs`, where `lhs` may be empty
+    /// to continue the previous line
+    pub fn relation(lhs: Option<Expr>, op: &str, rhs: impl Into<Expr>) -> Self {
+        let lhs = lhs.unwrap_or(Expr::Seq(Vec::new()));
+        let rhs = Expr::Rel(
+            Box::new(Expr::Seq(Vec::new())),
+            op.to_string(),
+            Box::new(rhs.into()),
+        );
+        Self {
+            cells: vec![lhs, rhs],
+            label: None,
+            nonumber: false,
+        }
+    }
+    /// Labels the line
+    pub fn label(mut self, label: Label) -> Self {
+        self.label = Some(label);
+        self
+    }
+    /// Leaves the line without a number using `\nonumber`
+    pub fn nonumber(mut self) -> Self {
+        self.nonumber = true;
+        self
+    }
+}
+
+impl Tex for MathLine {
+    fn to_latex_string(&self) -> String {
+        let cells: Vec<String> = self
+            .cells
+            .iter()
+            .map(|c| c.to_latex_string().trim().to_string())
+            .collect();
+        let mut line = cells.join(" &");
+        if let Some(label) = &self.label {
+            line.push_str(&format!(" {}", label.to_latex_string()))
+        }
+        if self.nonumber {
+            line.push_str(r" \nonumber")
+        }
+        line
+    }
+}
+
+/// A multi-line math environment like `align`, which requires `amsmath`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiLine {
+    pub kind: MultiLineKind,
+    pub starred: bool,
+    pub lines: Vec<MathLine>,
+}
+
+impl MultiLine {
+    pub fn new(kind: MultiLineKind, starred: bool) -> Self {
+        Self {
+            kind,
+            starred,
+            lines: Vec::new(),
+        }
+    }
+    /// Pushes a line
+    pub fn push(&mut self, line: MathLine) {
+        self.lines.push(line)
+    }
+    /// Pushes a line vector
+    pub fn push_array(&mut self, lines: Vec<MathLine>) {
+        self.lines.extend(lines)
+    }
+    /// Returns an `Error::Validation` if a line of `gather` or `multline` has an alignment point,
+    /// or a line of an environment without numbers has a label
+    pub fn check(&self) -> Result<(), Error> {
+        let problems = self.problems();
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Validation(problems.join("\n")))
+        }
+    }
+    // the lines the environment doesn't accept
+    fn problems(&self) -> Vec<String> {
+        let name = self.kind.name(self.starred);
+        let aligned = !matches!(self.kind, MultiLineKind::Gather | MultiLineKind::Multline);
+        let numbered = !self.starred && self.kind != MultiLineKind::Split;
+        let mut problems = Vec::new();
+        for (index, line) in self.lines.iter().enumerate() {
+            if !aligned && alignment_points(&line.to_latex_string()) > 0 {
+                problems.push(format!("line {index} of `{name}` has an alignment point"))
+            }
+            if !numbered && line.label.is_some() {
+                problems.push(format!(
+                    "line {index} of `{name}` has a label but no number"
+                ))
+            }
+        }
+        problems
+    }
+}
+
+// the number of `&` outside of nested environments, like the columns of a matrix
+fn alignment_points(latex: &str) -> usize {
+    let mut depth = 0usize;
+    let mut points = 0;
+    let mut rest = latex;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with(r"\begin{") {
+            depth += 1
+        } else if rest.starts_with(r"\end{") {
+            depth = depth.saturating_sub(1)
+        } else if c == '&' && depth == 0 {
+            points += 1
+        }
+        // an escaped character is never an alignment point
+        let skip = if c == '\\' {
+            1 + rest[1..].chars().next().map_or(0, char::len_utf8)
+        } else {
+            c.len_utf8()
+        };
+        rest = &rest[skip..]
+    }
+    points
+}
+
+impl Tex for MultiLine {
+    fn to_latex_string(&self) -> String {
+        let name = self.kind.name(self.starred);
+        let begin = match self.kind {
+            MultiLineKind::AlignAt(pairs) => format!(r"\begin{{{name}}}{{{pairs}}}"),
+            _ => format!(r"\begin{{{name}}}"),
+        };
+        let lines: Vec<String> = self.lines.iter().map(|l| l.to_latex_string()).collect();
+        format!("{begin}\n{}\n\\end{{{name}}}", lines.join(" \\\\\n"))
+    }
+}
+
+impl Requirements for MultiLine {
+    fn requirements(&self) -> Vec<Package> {
+        vec![Package::new("amsmath")]
+    }
+}
+
+/// Places `split` within `equation`, which is the environment of the element
+impl From<MultiLine> for Element<Any> {
+    fn from(value: MultiLine) -> Self {
+        let requires = value.requirements();
+        let problems = value.problems();
+        let mut name = value.kind.name(value.starred);
+        let mut latex = value.to_latex_string();
+        if value.kind == MultiLineKind::Split {
+            name = if value.starred {
+                "equation*"
+            } else {
+                "equation"
+            }
+            .to_string();
+            latex = format!("\\begin{{{name}}}\n{latex}\n\\end{{{name}}}");
+        }
+        let any = Any {
+            value: name,
+            latex,
+            type_: Type::T_Environment,
+            level: Level::Document,
+            header_level: None,
+            text_type: None,
+            list_type: None,
+            items: None,
+            elements: None,
+            package_options: None,
+            modified: false,
+        };
+        Element::new_any(any)
+            .requiring(requires)
+            .reporting(problems)
+    }
+}
+
+/// Piecewise definitions using the `cases` environment, which requires `amsmath`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cases {
+    pub rows: Vec<(Expr, Expr)>,
+}
+
+impl Cases {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Adds a value with the condition it applies under
+    pub fn row(mut self, value: impl Into<Expr>, condition: impl Into<Expr>) -> Self {
+        self.rows.push((value.into(), condition.into()));
+        self
+    }
+}
+
+impl Tex for Cases {
+    fn to_latex_string(&self) -> String {
+        let rows: Vec<String> = self
+            .rows
+            .iter()
+            .map(|(value, condition)| {
+                let value = value.to_latex_string();
+                format!("{value} & {}", condition.to_latex_string())
+            })
+            .collect();
+        format!("\\begin{{cases}}\n{}\n\\end{{cases}}", rows.join(" \\\\\n"))
+    }
+}
+
+/// Lets cases be the right hand side of an expression, like `f(x) = cases`
+impl From<Cases> for Expr {
+    fn from(value: Cases) -> Self {
+        Expr::Cases(value)
+    }
+}
@@ -0,0 +1,443 @@
+use super::Expr;
+use crate::{Any, Element, Level, Package, Requirements, Tex, Type};
+use serde::Serialize;
+
+/// The groups of the symbol catalogue
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+pub enum Category {
+    Greek,
+    Binary,
+    Relation,
+    Arrow,
+    Misc,
+    Dots,
+    BigOperator,
+    Delimiter,
+    /// Accents placed over or under their argument, like `\hat{x}`
+    Accent,
+    /// Fonts applied to their argument, like `\mathbb{R}`
+    Alphabet,
+}
+
+/// A math symbol of the catalogue, with the unicode character it stands for
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+pub struct MathSymbol {
+    /// The name of the command without the backslash
+    pub name: &'static str,
+    pub unicode: Option<char>,
+    pub category: Category,
+    pub package: Option<&'static str>,
+}
+
+impl MathSymbol {
+    /// Whether the symbol is a command taking an argument, like accents and alphabets
+    pub fn takes_argument(&self) -> bool {
+        matches!(self.category, Category::Accent | Category::Alphabet)
+    }
+    /// Applies an accent or alphabet to an argument, like `\mathbb{R}`
+    pub fn apply(&self, arg: impl Into<Expr>) -> Expr {
+        let package = self.package.map(|p| p.to_string());
+        Expr::Command(self.name.to_string(), Box::new(arg.into()), package)
+    }
+}
+
+impl Tex for MathSymbol {
+    fn to_latex_string(&self) -> String {
+        format!(r"\{}", self.name)
+    }
+}
+
+impl Requirements for MathSymbol {
+    fn requirements(&self) -> Vec<Package> {
+        self.package.map(Package::new).into_iter().collect()
+    }
+}
+
+impl From<MathSymbol> for Element<Any> {
+    fn from(value: MathSymbol) -> Self {
+        let requires = value.requirements();
+        let latex = value.to_latex_string();
+        let any = Any {
+            value: value.name.to_string(),
+            latex,
+            type_: Type::T_Bundle,
+            level: Level::Document,
+            header_level: None,
+            text_type: None,
+            list_type: None,
+            items: None,
+            elements: None,
+            package_options: None,
+            modified: false,
+        };
+        Element::new_any(any).requiring(requires)
+    }
+}
+
+impl From<MathSymbol> for Expr {
+    fn from(value: MathSymbol) -> Self {
+        Expr::Symbol(
+            value.to_latex_string(),
+            value.package.map(|p| p.to_string()),
+        )
+    }
+}
+
+impl From<super::Misc> for Element<Any> {
+    fn from(value: super::Misc) -> Self {
+        let requires = value.requirements();
+        let latex = value.to_latex_string();
+        let any = Any {
+            value: String::new(),
+            latex,
+            type_: Type::T_Bundle,
+            level: Level::Document,
+            header_level: None,
+            text_type: None,
+            list_type: None,
+            items: None,
+            elements: None,
+            package_options: None,
+            modified: false,
+        };
+        Element::new_any(any).requiring(requires)
+    }
+}
+
+/// Returns the symbol with the name, with or without the leading backslash
+pub fn lookup(name: &str) -> Option<MathSymbol> {
+    let name = name.strip_prefix('\\').unwrap_or(name);
+    SYMBOLS.iter().find(|s| s.name == name).copied()
+}
+
+/// Returns the symbols of a category
+pub fn symbols(category: Category) -> impl Iterator<Item = MathSymbol> {
+    SYMBOLS
+        .iter()
+        .filter(move |s| s.category == category)
+        .copied()
+}
+
+const fn sym(name: &'static str, unicode: char, category: Category) -> MathSymbol {
+    MathSymbol {
+        name,
+        unicode: Some(unicode),
+        category,
+        package: None,
+    }
+}
+
+const fn ams(name: &'static str, unicode: char, category: Category) -> MathSymbol {
+    MathSymbol {
+        name,
+        unicode: Some(unicode),
+        category,
+        package: Some("amssymb"),
+    }
+}
+
+const fn with(
+    name: &'static str,
+    unicode: char,
+    category: Category,
+    package: &'static str,
+) -> MathSymbol {
+    MathSymbol {
+        name,
+        unicode: Some(unicode),
+        category,
+        package: Some(package),
+    }
+}
+
+const fn cmd(name: &'static str, category: Category, package: Option<&'static str>) -> MathSymbol {
+    MathSymbol {
+        name,
+        unicode: None,
+        category,
+        package,
+    }
+}
+
+use Category::*;
+
+/// The symbol catalogue, following the [OverLeaf Guide](https://www.overleaf.com/learn/latex/List_of_Greek_letters_and_math_symbols).
+///
+/// Aliases sharing a character come after the preferred name.
+pub const SYMBOLS: &[MathSymbol] = &[
+    // greek letters
+    sym("alpha", 'α', Greek),
+    sym("beta", 'β', Greek),
+    sym("gamma", 'γ', Greek),
+    sym("delta", 'δ', Greek),
+    sym("varepsilon", 'ε', Greek),
+    sym("epsilon", 'ϵ', Greek),
+    sym("zeta", 'ζ', Greek),
+    sym("eta", 'η', Greek),
+    sym("theta", 'θ', Greek),
+    sym("vartheta", 'ϑ', Greek),
+    sym("iota", 'ι', Greek),
+    sym("kappa", 'κ', Greek),
+    ams("varkappa", 'ϰ', Greek),
+    sym("lambda", 'λ', Greek),
+    sym("mu", 'μ', Greek),
+    sym("nu", 'ν', Greek),
+    sym("xi", 'ξ', Greek),
+    sym("pi", 'π', Greek),
+    sym("varpi", 'ϖ', Greek),
+    sym("rho", 'ρ', Greek),
+    sym("varrho", 'ϱ', Greek),
+    sym("sigma", 'σ', Greek),
+    sym("varsigma", 'ς', Greek),
+    sym("tau", 'τ', Greek),
+    sym("upsilon", 'υ', Greek),
+    sym("varphi", 'φ', Greek),
+    sym("phi", 'ϕ', Greek),
+    sym("chi", 'χ', Greek),
+    sym("psi", 'ψ', Greek),
+    sym("omega", 'ω', Greek),
+    ams("digamma", 'ϝ', Greek),
+    sym("Gamma", 'Γ', Greek),
+    sym("Delta", 'Δ', Greek),
+    sym("Theta", 'Θ', Greek),
+    sym("Lambda", 'Λ', Greek),
+    sym("Xi", 'Ξ', Greek),
+    sym("Pi", 'Π', Greek),
+    sym("Sigma", 'Σ', Greek),
+    sym("Upsilon", 'Υ', Greek),
+    sym("Phi", 'Φ', Greek),
+    sym("Psi", 'Ψ', Greek),
+    sym("Omega", 'Ω', Greek),
+    // binary operators
+    sym("pm", '±', Binary),
+    sym("mp", '∓', Binary),
+    sym("times", '×', Binary),
+    sym("div", '÷', Binary),
+    sym("cdot", '⋅', Binary),
+    sym("ast", '∗', Binary),
+    sym("star", '⋆', Binary),
+    sym("circ", '∘', Binary),
+    sym("bullet", '∙', Binary),
+    sym("cap", '∩', Binary),
+    sym("cup", '∪', Binary),
+    sym("uplus", '⊎', Binary),
+    sym("sqcap", '⊓', Binary),
+    sym("sqcup", '⊔', Binary),
+    sym("vee", '∨', Binary),
+    sym("lor", '∨', Binary),
+    sym("wedge", '∧', Binary),
+    sym("land", '∧', Binary),
+    sym("setminus", '∖', Binary),
+    sym("wr", '≀', Binary),
+    sym("diamond", '⋄', Binary),
+    sym("bigtriangleup", '△', Binary),
+    sym("bigtriangledown", '▽', Binary),
+    sym("triangleleft", '◁', Binary),
+    sym("triangleright", '▷', Binary),
+    sym("oplus", '⊕', Binary),
+    sym("ominus", '⊖', Binary),
+    sym("otimes", '⊗', Binary),
+    sym("oslash", '⊘', Binary),
+    sym("odot", '⊙', Binary),
+    sym("bigcirc", '◯', Binary),
+    sym("dagger", '†', Binary),
+    sym("ddagger", '‡', Binary),
+    sym("amalg", '⨿', Binary),
+    ams("boxplus", '⊞', Binary),
+    ams("boxminus", '⊟', Binary),
+    ams("boxtimes", '⊠', Binary),
+    ams("boxdot", '⊡', Binary),
+    // relations
+    sym("leq", '≤', Relation),
+    sym("le", '≤', Relation),
+    sym("geq", '≥', Relation),
+    sym("ge", '≥', Relation),
+    sym("neq", '≠', Relation),
+    sym("ne", '≠', Relation),
+    sym("equiv", '≡', Relation),
+    sym("approx", '≈', Relation),
+    sym("cong", '≅', Relation),
+    sym("simeq", '≃', Relation),
+    sym("sim", '∼', Relation),
+    sym("propto", '∝', Relation),
+    sym("prec", '≺', Relation),
+    sym("succ", '≻', Relation),
+    sym("preceq", '⪯', Relation),
+    sym("succeq", '⪰', Relation),
+    sym("ll", '≪', Relation),
+    sym("gg", '≫', Relation),
+    sym("subset", '⊂', Relation),
+    sym("supset", '⊃', Relation),
+    sym("subseteq", '⊆', Relation),
+    sym("supseteq", '⊇', Relation),
+    sym("sqsubseteq", '⊑', Relation),
+    sym("sqsupseteq", '⊒', Relation),
+    sym("in", '∈', Relation),
+    sym("ni", '∋', Relation),
+    sym("notin", '∉', Relation),
+    sym("vdash", '⊢', Relation),
+    sym("dashv", '⊣', Relation),
+    sym("models", '⊨', Relation),
+    sym("perp", '⊥', Relation),
+    sym("mid", '∣', Relation),
+    sym("parallel", '∥', Relation),
+    sym("asymp", '≍', Relation),
+    sym("doteq", '≐', Relation),
+    sym("bowtie", '⋈', Relation),
+    sym("smile", '⌣', Relation),
+    sym("frown", '⌢', Relation),
+    ams("leqslant", '⩽', Relation),
+    ams("geqslant", '⩾', Relation),
+    ams("nleq", '≰', Relation),
+    ams("ngeq", '≱', Relation),
+    ams("subsetneq", '⊊', Relation),
+    ams("supsetneq", '⊋', Relation),
+    ams("lesssim", '≲', Relation),
+    ams("gtrsim", '≳', Relation),
+    ams("triangleq", '≜', Relation),
+    ams("therefore", '∴', Relation),
+    ams("because", '∵', Relation),
+    // arrows
+    sym("rightarrow", '→', Arrow),
+    sym("to", '→', Arrow),
+    sym("leftarrow", '←', Arrow),
+    sym("gets", '←', Arrow),
+    sym("leftrightarrow", '↔', Arrow),
+    sym("Rightarrow", '⇒', Arrow),
+    sym("Leftarrow", '⇐', Arrow),
+    sym("Leftrightarrow", '⇔', Arrow),
+    sym("uparrow", '↑', Arrow),
+    sym("downarrow", '↓', Arrow),
+    sym("updownarrow", '↕', Arrow),
+    sym("Uparrow", '⇑', Arrow),
+    sym("Downarrow", '⇓', Arrow),
+    sym("Updownarrow", '⇕', Arrow),
+    sym("mapsto", '↦', Arrow),
+    sym("longmapsto", '⟼', Arrow),
+    sym("longrightarrow", '⟶', Arrow),
+    sym("longleftarrow", '⟵', Arrow),
+    sym("longleftrightarrow", '⟷', Arrow),
+    sym("Longrightarrow", '⟹', Arrow),
+    sym("Longleftarrow", '⟸', Arrow),
+    sym("Longleftrightarrow", '⟺', Arrow),
+    sym("iff", '⟺', Arrow),
+    sym("hookrightarrow", '↪', Arrow),
+    sym("hookleftarrow", '↩', Arrow),
+    sym("nearrow", '↗', Arrow),
+    sym("searrow", '↘', Arrow),
+    sym("swarrow", '↙', Arrow),
+    sym("nwarrow", '↖', Arrow),
+    sym("leftharpoonup", '↼', Arrow),
+    sym("leftharpoondown", '↽', Arrow),
+    sym("rightharpoonup", '⇀', Arrow),
+    sym("rightharpoondown", '⇁', Arrow),
+    sym("rightleftharpoons", '⇌', Arrow),
+    // miscellaneous symbols
+    sym("infty", '∞', Misc),
+    sym("forall", '∀', Misc),
+    sym("exists", '∃', Misc),
+    ams("nexists", '∄', Misc),
+    sym("partial", '∂', Misc),
+    sym("nabla", '∇', Misc),
+    sym("emptyset", '∅', Misc),
+    ams("varnothing", '∅', Misc),
+    sym("Re", 'ℜ', Misc),
+    sym("Im", 'ℑ', Misc),
+    sym("wp", '℘', Misc),
+    sym("aleph", 'ℵ', Misc),
+    ams("beth", 'ℶ', Misc),
+    sym("hbar", 'ℏ', Misc),
+    sym("ell", 'ℓ', Misc),
+    sym("imath", 'ı', Misc),
+    sym("jmath", 'ȷ', Misc),
+    sym("neg", '¬', Misc),
+    sym("lnot", '¬', Misc),
+    sym("angle", '∠', Misc),
+    ams("measuredangle", '∡', Misc),
+    sym("prime", '′', Misc),
+    sym("surd", '√', Misc),
+    sym("top", '⊤', Misc),
+    sym("bot", '⊥', Misc),
+    ams("complement", '∁', Misc),
+    ams("square", '□', Misc),
+    ams("Box", '□', Misc),
+    ams("blacksquare", '■', Misc),
+    sym("triangle", '△', Misc),
+    sym("clubsuit", '♣', Misc),
+    sym("diamondsuit", '♢', Misc),
+    sym("heartsuit", '♡', Misc),
+    sym("spadesuit", '♠', Misc),
+    sym("flat", '♭', Misc),
+    sym("natural", '♮', Misc),
+    sym("sharp", '♯', Misc),
+    ams("checkmark", '✓', Misc),
+    // dots
+    sym("cdots", '⋯', Dots),
+    sym("ldots", '…', Dots),
+    sym("vdots", '⋮', Dots),
+    sym("ddots", '⋱', Dots),
+    cmd("dots", Dots, Some("amsmath")),
+    // big operators
+    sym("sum", '∑', BigOperator),
+    sym("prod", '∏', BigOperator),
+    sym("coprod", '∐', BigOperator),
+    sym("int", '∫', BigOperator),
+    with("iint", '∬', BigOperator, "amsmath"),
+    with("iiint", '∭', BigOperator, "amsmath"),
+    sym("oint", '∮', BigOperator),
+    sym("bigcup", '⋃', BigOperator),
+    sym("bigcap", '⋂', BigOperator),
+    sym("bigsqcup", '⨆', BigOperator),
+    sym("bigvee", '⋁', BigOperator),
+    sym("bigwedge", '⋀', BigOperator),
+    sym("bigoplus", '⨁', BigOperator),
+    sym("bigotimes", '⨂', BigOperator),
+    sym("bigodot", '⨀', BigOperator),
+    sym("biguplus", '⨄', BigOperator),
+    // delimiters
+    sym("langle", '⟨', Delimiter),
+    sym("rangle", '⟩', Delimiter),
+    sym("lfloor", '⌊', Delimiter),
+    sym("rfloor", '⌋', Delimiter),
+    sym("lceil", '⌈', Delimiter),
+    sym("rceil", '⌉', Delimiter),
+    sym("Vert", '‖', Delimiter),
+    cmd("lbrace", Delimiter, None),
+    cmd("rbrace", Delimiter, None),
+    cmd("vert", Delimiter, None),
+    cmd("lvert", Delimiter, Some("amsmath")),
+    cmd("rvert", Delimiter, Some("amsmath")),
+    cmd("lVert", Delimiter, Some("amsmath")),
+    cmd("rVert", Delimiter, Some("amsmath")),
+    cmd("backslash", Delimiter, None),
+    // accents, with their combining characters
+    sym("hat", '\u{0302}', Accent),
+    sym("check", '\u{030C}', Accent),
+    sym("tilde", '\u{0303}', Accent),
+    sym("acute", '\u{0301}', Accent),
+    sym("grave", '\u{0300}', Accent),
+    sym("dot", '\u{0307}', Accent),
+    sym("ddot", '\u{0308}', Accent),
+    sym("breve", '\u{0306}', Accent),
+    sym("bar", '\u{0304}', Accent),
+    sym("vec", '\u{20D7}', Accent),
+    sym("overline", '\u{0305}', Accent),
+    sym("underline", '\u{0332}', Accent),
+    cmd("widehat", Accent, None),
+    cmd("widetilde", Accent, None),
+    cmd("overrightarrow", Accent, None),
+    cmd("overleftarrow", Accent, None),
+    cmd("overbrace", Accent, None),
+    cmd("underbrace", Accent, None),
+    // math alphabets
+    cmd("mathrm", Alphabet, None),
+    cmd("mathit", Alphabet, None),
+    cmd("mathbf", Alphabet, None),
+    cmd("mathsf", Alphabet, None),
+    cmd("mathtt", Alphabet, None),
+    cmd("mathcal", Alphabet, None),
+    cmd("mathbb", Alphabet, Some("amssymb")),
+    cmd("mathfrak", Alphabet, Some("amssymb")),
+    cmd("mathscr", Alphabet, Some("mathrsfs")),
+    cmd("boldsymbol", Alphabet, Some("amsmath")),
+];
@@ -0,0 +1,297 @@
+use super::mathml::{command_mathml, xml};
+use super::{Category, Expr, MathMl, MathSymbol, SYMBOLS};
+use crate::TextType::Math;
+use crate::{Any, Element, Package, Requirements, Tex, Text};
+use serde::{Deserialize, Serialize};
+
+/// Math written with unicode characters, like `α² + β₁`, converted into latex.
+///
+/// Characters of the symbol catalogue become their commands, super and subscript
+/// digits become `^{}` and `_{}`, like an ascii `^` or `_` followed by letters or
+/// digits, so `x^2` gives `x^{2}` and `a_ij` gives `a_{ij}`. Combining accents are
+/// applied to the preceding symbol, capital greek letters without a command become
+/// the latin letters they look like and the latex special characters are escaped,
+/// so `%`, `&` or a `^` followed by a space is typeset as is. The remaining characters
+/// are kept as is.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnicodeMath {
+    pub input: String,
+}
+
+impl UnicodeMath {
+    pub fn new(input: &str) -> Self {
+        Self {
+            input: input.to_string(),
+        }
+    }
+    // converts the input, returning the latex and the packages it requires
+    fn convert(&self) -> (String, Vec<Package>) {
+        let mut out = String::new();
+        let mut packages: Vec<Package> = Vec::new();
+        // where the last symbol starts in `out`, for accents to apply to
+        let mut atom = 0;
+        // whether `out` ends with a control word, which a letter can't directly follow
+        let mut after_word = false;
+        let mut chars = self.input.chars().peekable();
+        while let Some(c) = chars.next() {
+            let script: Option<(char, Script)> = match c {
+                _ if superscript(c).is_some() => Some(('^', superscript)),
+                _ if subscript(c).is_some() => Some(('_', subscript)),
+                '^' | '_' if chars.peek().is_some_and(char::is_ascii_alphanumeric) => {
+                    Some((c, ascii_script))
+                }
+                _ => None,
+            };
+            if let Some((command, script)) = script {
+                let mut run = String::new();
+                run.extend(script(c));
+                while let Some(next) = chars.peek().and_then(|n| script(*n)) {
+                    run.push(next);
+                    chars.next();
+                }
+                out.push_str(&format!("{command}{{{run}}}"));
+                after_word = false;
+                continue;
+            }
+            if let Some(accent) = reverse(c, true) {
+                let base = out.split_off(atom);
+                out.push_str(&format!(r"{}{{{}}}", accent.to_latex_string(), base.trim()));
+                after_word = false;
+                continue;
+            }
+            let (text, word, package) = match c {
+                '−' => ("-".to_string(), false, None),
+                '·' => (r"\cdot".to_string(), true, None),
+                '′' => ("'".to_string(), false, None),
+                '″' => ("''".to_string(), false, None),
+                '&' | '%' | '$' | '#' | '_' | '{' | '}' => (format!(r"\{c}"), false, None),
+                '~' => (r"\sim".to_string(), true, None),
+                '^' => (r"\hat{}".to_string(), false, None),
+                '\\' => (r"\backslash".to_string(), true, None),
+                _ => match alphabet(c) {
+                    Some((font, letter, package)) => {
+                        (format!(r"\{font}{{{letter}}}"), false, package)
+                    }
+                    None => match reverse(c, false) {
+                        Some(symbol) => (symbol.to_latex_string(), true, symbol.package),
+                        None => (greek_capital(c).unwrap_or(c).to_string(), false, None),
+                    },
+                },
+            };
+            if after_word && text.starts_with(|ch: char| ch.is_ascii_alphabetic()) {
+                out.push(' ')
+            }
+            atom = out.len();
+            out.push_str(&text);
+            after_word = word;
+            if let Some(package) = package {
+                let package = Package::new(package);
+                if !packages.contains(&package) {
+                    packages.push(package)
+                }
+            }
+        }
+        (out, packages)
+    }
+}
+
+impl Tex for UnicodeMath {
+    fn to_latex_string(&self) -> String {
+        self.convert().0
+    }
+}
+
+impl Requirements for UnicodeMath {
+    fn requirements(&self) -> Vec<Package> {
+        self.convert().1
+    }
+}
+
+/// Converts into inline math, like `$\alpha^{2}$`
+impl From<UnicodeMath> for Element<Any> {
+    fn from(value: UnicodeMath) -> Self {
+        let (latex, requires) = value.convert();
+        Element::from(Text::raw(&latex, Math)).requiring(requires)
+    }
+}
+
+impl From<UnicodeMath> for Expr {
+    fn from(value: UnicodeMath) -> Self {
+        let (latex, requires) = value.convert();
+        Expr::Symbol(latex, requires.first().map(|p| p.pkg.to_string()))
+    }
+}
+
+/// Keeps the unicode characters, marking them up as identifiers, numbers and operators
+impl MathMl for UnicodeMath {
+    fn to_mathml(&self) -> String {
+        let mut items: Vec<String> = Vec::new();
+        let mut chars = self.input.chars().peekable();
+        while let Some(c) = chars.next() {
+            let script: Option<(&str, Script)> = match c {
+                _ if superscript(c).is_some() => Some(("msup", superscript)),
+                _ if subscript(c).is_some() => Some(("msub", subscript)),
+                '^' if chars.peek().is_some_and(char::is_ascii_alphanumeric) => {
+                    Some(("msup", ascii_script))
+                }
+                '_' if chars.peek().is_some_and(char::is_ascii_alphanumeric) => {
+                    Some(("msub", ascii_script))
+                }
+                _ => None,
+            };
+            if let Some((tag, script)) = script {
+                let mut run = String::new();
+                run.extend(script(c));
+                while let Some(next) = chars.peek().and_then(|n| script(*n)) {
+                    run.push(next);
+                    chars.next();
+                }
+                let base = items.pop().unwrap_or_else(|| "<mrow></mrow>".to_string());
+                let run = UnicodeMath::new(&run).to_mathml();
+                items.push(format!("<{tag}>{base}{run}</{tag}>"));
+                continue;
+            }
+            if let Some(accent) = reverse(c, true) {
+                let base = items.pop().unwrap_or_else(|| "<mrow></mrow>".to_string());
+                items.push(command_mathml(accent.name, &base));
+                continue;
+            }
+            let item = match c {
+                _ if c.is_whitespace() => continue,
+                _ if c.is_ascii_digit() || c == '.' => {
+                    let mut number = String::from(c);
+                    while let Some(next) = chars.next_if(|n| n.is_ascii_digit() || *n == '.') {
+                        number.push(next)
+                    }
+                    format!("<mn>{number}</mn>")
+                }
+                _ => match reverse(c, false) {
+                    Some(symbol) if matches!(symbol.category, Category::Greek | Category::Misc) => {
+                        format!("<mi>{c}</mi>")
+                    }
+                    Some(_) => format!("<mo>{c}</mo>"),
+                    None if c.is_alphabetic() => format!("<mi>{c}</mi>"),
+                    None => format!("<mo>{}</mo>", xml(&c.to_string())),
+                },
+            };
+            items.push(item)
+        }
+        format!("<mrow>{}</mrow>", items.concat())
+    }
+}
+
+/// Converts math written with unicode characters into latex,
+/// where `x^2` and `a_i` become scripts like `x²` and `aᵢ`
+pub fn unicode_to_latex(input: &str) -> String {
+    UnicodeMath::new(input).to_latex_string()
+}
+
+// the first symbol of the catalogue standing for the character, either an accent or not
+fn reverse(c: char, accent: bool) -> Option<MathSymbol> {
+    SYMBOLS
+        .iter()
+        .find(|s| s.unicode == Some(c) && s.takes_argument() == accent)
+        .copied()
+}
+
+// letters of the double-struck and script alphabets with their font and package
+fn alphabet(c: char) -> Option<(&'static str, char, Option<&'static str>)> {
+    let letter = match c {
+        'ℂ' => ('C', true),
+        'ℍ' => ('H', true),
+        'ℕ' => ('N', true),
+        'ℙ' => ('P', true),
+        'ℚ' => ('Q', true),
+        'ℝ' => ('R', true),
+        'ℤ' => ('Z', true),
+        'ℬ' => ('B', false),
+        'ℰ' => ('E', false),
+        'ℱ' => ('F', false),
+        'ℋ' => ('H', false),
+        'ℐ' => ('I', false),
+        'ℒ' => ('L', false),
+        'ℳ' => ('M', false),
+        'ℛ' => ('R', false),
+        _ => return None,
+    };
+    match letter {
+        (l, true) => Some(("mathbb", l, Some("amssymb"))),
+        (l, false) => Some(("mathcal", l, None)),
+    }
+}
+
+// the latin letters standing for the capital greek letters latex has no command for
+fn greek_capital(c: char) -> Option<char> {
+    let latin = match c {
+        'Α' => 'A',
+        'Β' => 'B',
+        'Ε' => 'E',
+        'Ζ' => 'Z',
+        'Η' => 'H',
+        'Ι' => 'I',
+        'Κ' => 'K',
+        'Μ' => 'M',
+        'Ν' => 'N',
+        'Ο' => 'O',
+        'Ρ' => 'P',
+        'Τ' => 'T',
+        'Χ' => 'X',
+        _ => return None,
+    };
+    Some(latin)
+}
+
+// maps a super or subscript character to the one it raises or lowers
+type Script = fn(char) -> Option<char>;
+
+// the letters and digits after an ascii `^` or `_`
+fn ascii_script(c: char) -> Option<char> {
+    c.is_ascii_alphanumeric().then_some(c)
+}
+
+fn superscript(c: char) -> Option<char> {
+    match c {
+        '⁰' => Some('0'),
+        '¹' => Some('1'),
+        '²' => Some('2'),
+        '³' => Some('3'),
+        '⁴'..='⁹' => char::from_digit(c as u32 - '⁰' as u32, 10),
+        '⁺' => Some('+'),
+        '⁻' => Some('-'),
+        '⁼' => Some('='),
+        '⁽' => Some('('),
+        '⁾' => Some(')'),
+        'ⁿ' => Some('n'),
+        'ⁱ' => Some('i'),
+        _ => None,
+    }
+}
+
+fn subscript(c: char) -> Option<char> {
+    match c {
+        '₀'..='₉' => char::from_digit(c as u32 - '₀' as u32, 10),
+        '₊' => Some('+'),
+        '₋' => Some('-'),
+        '₌' => Some('='),
+        '₍' => Some('('),
+        '₎' => Some(')'),
+        'ₐ' => Some('a'),
+        'ₑ' => Some('e'),
+        'ₒ' => Some('o'),
+        'ₓ' => Some('x'),
+        'ₕ' => Some('h'),
+        'ₖ' => Some('k'),
+        'ₗ' => Some('l'),
+        'ₘ' => Some('m'),
+        'ₙ' => Some('n'),
+        'ₚ' => Some('p'),
+        'ₛ' => Some('s'),
+        'ₜ' => Some('t'),
+        'ᵢ' => Some('i'),
+        'ⱼ' => Some('j'),
+        'ᵣ' => Some('r'),
+        'ᵤ' => Some('u'),
+        'ᵥ' => Some('v'),
+        _ => None,
+    }
+}
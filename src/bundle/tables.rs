@@ -1,4 +1,8 @@
-use crate::{Any, Element, Elements, Environment, Package, Tex, Text, TextType};
+use crate::{
+    escape, Any, Element, Elements, Environment, Error, Label, Package, PackageOption,
+    Requirements, Tex, Text, TextType,
+};
+pub use texcore_traits::TableRow;
 use texcore_traits::{ExtraOptions, Options};
 
 /// A wrapper over the `Environment` element that provides a better approach to create tables.
@@ -9,12 +13,90 @@ pub struct Table {
     extension: bool,
     op_pkgs: bool,
     width: Option<f64>,
+    style: TableStyle,
+    header: usize,
+    stripes: Option<(String, String)>,
+    long: bool,
+    /// The text at the bottom of every page of a long table but the last
+    continued: String,
+    caption: Option<String>,
+    label: Option<Label>,
 }
 
+/// How the rules of a table are drawn
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum TableStyle {
+    /// `\hline`, `\cline` and the vertical separators of the columns
+    #[default]
+    Grid,
+    /// `\toprule`, `\midrule`, `\bottomrule` and `\cmidrule` from `booktabs`,
+    /// without vertical rules
+    Booktabs,
+}
+
+/// Reports the rows that don't fill the columns and the caption or label that can't be placed,
+/// which `ElementList::validate()` returns.
+///
+/// The elements of the cells are nested in the element, which requires their packages.
 impl From<Table> for Element<Any> {
     fn from(value: Table) -> Self {
+        let requires = value.own_requirements();
+        let problems = value.problems();
         let env = value.build_table();
-        Self::from(env)
+        let mut element = Self::from(env).requiring(requires).reporting(problems);
+        let cells = value.rows.iter().flat_map(|r| &r.cells);
+        element.value.elements = Some(cells.flat_map(|c| c.element.clone()).collect());
+        if let Some(stripes) = value.stripes_latex() {
+            element.latex = format!("{stripes}\n{}", element.latex);
+            element.value.latex = element.latex.to_string();
+        }
+        // the label follows the first caption, and is left out without one
+        let label = value
+            .label
+            .filter(|_| value.long && value.caption.is_some());
+        element.labelled_at_last(label)
+    }
+}
+
+/// Requires `array` for middle or bottom aligned columns, `multirow` for cells spanning rows,
+/// `booktabs` for its style, `xcolor` with the `table` option for stripes
+/// and the packages the elements of its cells require
+impl Requirements for Table {
+    fn requirements(&self) -> Vec<Package> {
+        let mut packages = self.own_requirements();
+        for row in &self.rows {
+            packages.extend(row.requirements())
+        }
+        packages
+    }
+}
+
+impl Table {
+    // the packages of the table itself, without the ones of its cells
+    fn own_requirements(&self) -> Vec<Package> {
+        let mut packages = Vec::new();
+        if self.long {
+            packages.push(Package::new("longtable"))
+        }
+        if self.style == TableStyle::Booktabs {
+            packages.push(Package::new("booktabs"))
+        }
+        if self.stripes.is_some() {
+            let table = PackageOption::Flag("table".to_string());
+            packages.push(Package::with_options("xcolor", vec![table]))
+        }
+        let array = self
+            .col_pos
+            .iter()
+            .any(|c| matches!(c.pos, Position::Middle(_) | Position::Bottom(_)));
+        if array {
+            packages.push(Package::new("array"))
+        }
+        let multirow = self.rows.iter().flat_map(|r| &r.cells).any(|c| c.rows > 1);
+        if multirow {
+            packages.push(Package::new("multirow"))
+        }
+        packages
     }
 }
 
@@ -32,8 +114,180 @@ impl Table {
             rows,
             extension,
             op_pkgs,
+            style: TableStyle::Grid,
+            header: 0,
+            stripes: None,
+            long: false,
+            continued: "Continued on next page".to_string(),
+            caption: None,
+            label: None,
+        }
+    }
+    /// Renders a `longtable` that breaks across pages instead of a `tabular`,
+    /// repeating the header rows on every page, where the width and the extension are unused
+    pub fn long(mut self) -> Self {
+        self.long = true;
+        self
+    }
+    /// Sets the text at the bottom of every page of a long table but the last,
+    /// escaping its LaTeX special characters
+    pub fn continued(mut self, text: &str) -> Self {
+        self.continued = text.to_string();
+        self
+    }
+    /// Sets the caption of a long table, repeated on the following pages as continued,
+    /// escaping its LaTeX special characters.
+    ///
+    /// A `tabular` is captioned by the float around it instead, like a `Figure`,
+    /// so its caption is reported as a problem and left out.
+    pub fn caption(mut self, caption: &str) -> Self {
+        self.caption = Some(caption.to_string());
+        self
+    }
+    /// Sets the label of a long table, placed after its caption, which a label needs to refer to,
+    /// so it is reported as a problem and left out without one
+    pub fn label(mut self, label: Label) -> Self {
+        self.label = Some(label);
+        self
+    }
+    /// Sets how the rules are drawn
+    pub fn style(mut self, style: TableStyle) -> Self {
+        self.style = style;
+        self
+    }
+    /// Marks the first `rows` rows as the header, which `\midrule` separates from the body
+    /// in the booktabs style and which stripes leave out
+    pub fn header(mut self, rows: usize) -> Self {
+        self.header = rows;
+        self
+    }
+    /// Alternates the background of the body rows between two `xcolor` colors,
+    /// like `gray!10` and `white`, using `\rowcolors`
+    pub fn striped(mut self, odd: &str, even: &str) -> Self {
+        self.stripes = Some((odd.to_string(), even.to_string()));
+        self
+    }
+    // `\rowcolors{}` starting at the first row after the header
+    fn stripes_latex(&self) -> Option<String> {
+        let (odd, even) = self.stripes.as_ref()?;
+        Some(format!(
+            r"\rowcolors{{{}}}{{{odd}}}{{{even}}}",
+            self.header + 1
+        ))
+    }
+    // the columns as they are drawn, without vertical rules in the booktabs style
+    fn columns(&self) -> Vec<Column> {
+        match self.style {
+            TableStyle::Grid => self.col_pos.clone(),
+            TableStyle::Booktabs => self
+                .col_pos
+                .iter()
+                .map(|c| Column::new(c.pos, Separator::None).right(Separator::None))
+                .collect(),
         }
     }
+    // the rule drawn above the row at `index`
+    fn rule(&self, index: usize) -> String {
+        let rule = &self.rows[index].rule;
+        match self.style {
+            TableStyle::Grid => rule.to_latex_string(),
+            TableStyle::Booktabs if index == 0 => r"\toprule".to_string(),
+            TableStyle::Booktabs if index == self.header => r"\midrule".to_string(),
+            // the other full rules are left out, as booktabs tables only separate groups
+            TableStyle::Booktabs => match rule {
+                Rule::CLine(ranges) => ranges
+                    .iter()
+                    .map(|(a, b)| format!(r"\cmidrule(lr){{{a}-{b}}}"))
+                    .collect(),
+                Rule::HLine | Rule::None => String::new(),
+            },
+        }
+    }
+    /// Creates a table like `new()`, rejecting rows whose cells don't fill the columns
+    pub fn try_new(
+        width: Option<f64>,
+        col_pos: Vec<Column>,
+        rows: Vec<Row>,
+        extension: bool,
+        op_pkgs: bool,
+    ) -> Result<Self, Error> {
+        let table = Self::new(width, col_pos, rows, extension, op_pkgs);
+        table.check()?;
+        Ok(table)
+    }
+    /// Returns an `Error::Validation` if a row doesn't fill exactly the number of columns,
+    /// counting the columns covered by cells spanning rows from above,
+    /// or if the caption or the label can't be placed
+    pub fn check(&self) -> Result<(), Error> {
+        let problems = self.problems();
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Validation(problems.join("\n")))
+        }
+    }
+    // the problems laying out the cells, then the caption and the label that are left out
+    fn problems(&self) -> Vec<String> {
+        let (_, mut problems) = self.layout();
+        if !self.long && self.caption.is_some() {
+            problems.push("the caption of a `tabular` belongs to the float around it".to_string())
+        }
+        if self.label.is_some() && !(self.long && self.caption.is_some()) {
+            problems.push("the label of a table needs the caption of a long table".to_string())
+        }
+        problems
+    }
+    // the latex of each row and the problems found laying out the cells
+    fn layout(&self) -> (Vec<String>, Vec<String>) {
+        let col_pos = self.columns();
+        let columns = col_pos.len();
+        // the rows left and the width of cells spanning rows, by their first column
+        let mut covered: Vec<Option<(usize, usize)>> = vec![None; columns];
+        let mut rows = Vec::new();
+        let mut problems = Vec::new();
+        for (index, row) in self.rows.iter().enumerate() {
+            let mut cells = Vec::new();
+            let mut col = 0;
+            let mut row_cells = row.cells.iter();
+            loop {
+                if let Some(Some((left, width))) = covered.get(col).copied() {
+                    cells.push(placeholder(&col_pos, col, width));
+                    covered[col] = (left > 1).then_some((left - 1, width));
+                    col += width;
+                    continue;
+                }
+                let cell = match row_cells.next() {
+                    Some(cell) => cell,
+                    None => break,
+                };
+                cells.push(cell.render(&col_pos, col));
+                // the cell can't reach over the columns a cell above still covers
+                let end = (col + cell.cols).min(columns);
+                for (m, over) in covered.iter_mut().enumerate().take(end).skip(col + 1) {
+                    if let Some((left, width)) = *over {
+                        problems.push(format!(
+                            "row {index} overlaps a cell spanning rows at column {m}"
+                        ));
+                        *over = (left > 1).then_some((left - 1, width))
+                    }
+                }
+                if cell.rows > 1 && col < columns {
+                    covered[col] = Some((cell.rows - 1, cell.cols))
+                }
+                col += cell.cols;
+            }
+            if col != columns {
+                problems.push(format!(
+                    "row {index} fills {col} columns instead of {columns}"
+                ))
+            }
+            rows.push(format!("{} \\\\", cells.join(" & ")))
+        }
+        if covered.iter().any(|c| c.is_some()) {
+            problems.push("a cell spans rows past the last row".to_string())
+        }
+        (rows, problems)
+    }
     fn get_env(&self) -> Environment {
         if self.extension {
             let name = "tabular*";
@@ -43,13 +297,82 @@ impl Table {
             Environment::new(name)
         }
     }
+    // the rule above each row followed by the row, leaving out empty rules
+    fn lines(&self) -> Vec<Vec<String>> {
+        let (rows, _) = self.layout();
+        rows.into_iter()
+            .enumerate()
+            .map(|(index, latex)| {
+                let rule = self.rule(index);
+                [rule, latex]
+                    .into_iter()
+                    .filter(|l| !l.is_empty())
+                    .collect()
+            })
+            .collect()
+    }
+    // the rule closing the table
+    fn closing_rule(&self) -> &'static str {
+        match self.style {
+            TableStyle::Grid => r"\hline",
+            TableStyle::Booktabs => r"\bottomrule",
+        }
+    }
+    fn build_long(&self) -> Environment {
+        let mut env = Environment::new("longtable");
+        let mut push = |latex: &str| env.push(Element::from(Text::raw(latex, TextType::Normal)));
+        let mut lines = self.lines();
+        let header = self.header.min(lines.len());
+        let mut body = lines.split_off(header);
+        let mut head = lines.concat();
+        // the rule above the first body row ends the header on every page
+        if header > 0 && !body.is_empty() && body[0].len() > 1 {
+            head.push(body[0].remove(0))
+        }
+        let label = self.label.as_ref().map(|l| l.to_latex_string());
+        if let Some(caption) = &self.caption {
+            let caption = escape(caption);
+            let label = label.unwrap_or_default();
+            push(&format!("\\caption{{{caption}}}{label} \\\\"));
+            head.iter().for_each(|l| push(l));
+            push(r"\endfirsthead");
+            push(&format!("\\caption[]{{{caption} (continued)}} \\\\"));
+            head.iter().for_each(|l| push(l));
+            push(r"\endhead");
+        } else if !head.is_empty() {
+            head.iter().for_each(|l| push(l));
+            push(r"\endhead");
+        }
+        let columns = self.columns();
+        let spec = span_spec(&columns, 0, columns.len(), Some(Position::Right));
+        let continued = escape(&self.continued);
+        match self.style {
+            TableStyle::Grid => push(r"\hline"),
+            TableStyle::Booktabs => push(r"\midrule"),
+        }
+        push(&format!(
+            "\\multicolumn{{{}}}{{{spec}}}{{{continued}}} \\\\",
+            columns.len()
+        ));
+        push(r"\endfoot");
+        push(self.closing_rule());
+        push(r"\endlastfoot");
+        body.concat().iter().for_each(|l| push(l));
+        env.modify_element(vec![Options::Curly(columns.to_latex_string())]);
+        env
+    }
     fn build_table(&self) -> Environment {
+        if self.long {
+            return self.build_long();
+        }
         let mut env = self.get_env();
-        for row in &self.rows {
-            env.push(Element::from(Text::new(r"\hline", TextType::Normal)));
-            env.push(row.to_element())
+        for line in self.lines().concat() {
+            env.push(Element::from(Text::raw(&line, TextType::Normal)))
+        }
+        if self.style == TableStyle::Booktabs {
+            env.push(Element::from(Text::raw(r"\bottomrule", TextType::Normal)));
         }
-        let col_opt = Options::Curly(self.col_pos.to_latex_string());
+        let col_opt = Options::Curly(self.columns().to_latex_string());
         if self.extension {
             match self.width {
                 None => env.modify_element(vec![col_opt]),
@@ -61,7 +384,7 @@ impl Table {
             }
             env
         } else {
-            env.modify_element(vec![Options::Curly(self.col_pos.to_latex_string())]);
+            env.modify_element(vec![col_opt]);
             env
         }
     }
@@ -69,9 +392,9 @@ impl Table {
         if self.op_pkgs {
             let arr = Package::new("array");
             let tabx = Package::new("tabularx");
-            Elements![arr, tabx, self.build_table()]
+            Elements![arr, tabx, self.clone()]
         } else {
-            Elements![self.build_table()]
+            Elements![self.clone()]
         }
     }
 }
@@ -141,11 +464,37 @@ pub struct Column {
     pos: Position,
     /// Places a separator line to the left
     sep: Separator,
+    /// Places a separator line to the right, only used by the last column
+    right: Option<Separator>,
 }
 
 impl Column {
     pub fn new(pos: Position, sep: Separator) -> Self {
-        Self { pos, sep }
+        Self {
+            pos,
+            sep,
+            right: None,
+        }
+    }
+    /// Sets the separator to the right of the last column,
+    /// which otherwise matches the one to the left of the first column
+    pub fn right(mut self, sep: Separator) -> Self {
+        self.right = Some(sep);
+        self
+    }
+}
+
+// the separator closing the right side of the table
+fn trailing_separator(columns: &[Column]) -> String {
+    match (columns.first(), columns.last()) {
+        (
+            _,
+            Some(Column {
+                right: Some(sep), ..
+            }),
+        ) => sep.to_latex_string(),
+        (Some(first), _) => first.sep.to_latex_string(),
+        _ => String::new(),
     }
 }
 
@@ -163,41 +512,176 @@ impl Tex for Vec<Column> {
     fn to_latex_string(&self) -> String {
         let mut s = Vec::new();
         for c in self {
-            s.push(c.to_latex_string())
+            s.push(c.sep.to_latex_string());
+            s.push(c.pos.to_latex_string())
         }
-        // temporary fix
-        // TODO!: figure out last column separator right side
-        s.push("|".to_string());
+        s.push(trailing_separator(self));
+        s.retain(|p| !p.is_empty());
         s.join(" ")
     }
 }
 
+/// A cell of a table, which may span several columns or rows
+#[derive(Debug, Clone)]
+pub struct Cell {
+    content: String,
+    /// The element of the cell, kept for its packages and its checks
+    element: Option<Element<Any>>,
+    cols: usize,
+    rows: usize,
+    align: Option<Position>,
+}
+
+impl Cell {
+    pub fn new(element: Element<Any>) -> Self {
+        Self {
+            content: element.latex.to_string(),
+            element: Some(element),
+            cols: 1,
+            rows: 1,
+            align: None,
+        }
+    }
+    /// A cell without content
+    pub fn empty() -> Self {
+        Self {
+            content: String::new(),
+            element: None,
+            cols: 1,
+            rows: 1,
+            align: None,
+        }
+    }
+    /// Spans the cell over `cols` columns using `\multicolumn`
+    pub fn span_cols(mut self, cols: usize) -> Self {
+        self.cols = cols.max(1);
+        self
+    }
+    /// Spans the cell over `rows` rows using `\multirow`, which requires the `multirow` package.
+    ///
+    /// The rows below leave out the covered cells, which are filled in with empty ones.
+    pub fn span_rows(mut self, rows: usize) -> Self {
+        self.rows = rows.max(1);
+        self
+    }
+    /// Overrides the alignment of the column using `\multicolumn`
+    pub fn align(mut self, align: Position) -> Self {
+        self.align = Some(align);
+        self
+    }
+    // the latex of the cell starting at column `start`
+    fn render(&self, columns: &[Column], start: usize) -> String {
+        let mut content = self.content.to_string();
+        if self.rows > 1 {
+            content = format!(r"\multirow{{{}}}{{*}}{{{content}}}", self.rows)
+        }
+        if self.cols > 1 || self.align.is_some() {
+            let spec = span_spec(columns, start, self.cols, self.align);
+            format!(r"\multicolumn{{{}}}{{{spec}}}{{{content}}}", self.cols)
+        } else {
+            content
+        }
+    }
+}
+
+impl Tex for Cell {
+    fn to_latex_string(&self) -> String {
+        self.render(&[], 0)
+    }
+}
+
+// the column specification of `\multicolumn` for `width` columns from `start`,
+// keeping the separators around them
+fn span_spec(columns: &[Column], start: usize, width: usize, align: Option<Position>) -> String {
+    let left = match columns.first() {
+        Some(first) if start == 0 => first.sep.to_latex_string(),
+        _ => String::new(),
+    };
+    let pos = align
+        .or_else(|| columns.get(start).map(|c| c.pos))
+        .unwrap_or(Position::Centered);
+    let right = match columns.get(start + width) {
+        Some(next) => next.sep.to_latex_string(),
+        None => trailing_separator(columns),
+    };
+    format!("{left}{}{right}", pos.to_latex_string())
+}
+
+// the empty cell filling the columns covered by a cell spanning rows
+fn placeholder(columns: &[Column], start: usize, width: usize) -> String {
+    if width > 1 {
+        let spec = span_spec(columns, start, width, None);
+        format!(r"\multicolumn{{{width}}}{{{spec}}}{{}}")
+    } else {
+        String::new()
+    }
+}
+
+/// The horizontal rule drawn above a row
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+    /// `\hline` across the table
+    HLine,
+    /// `\cline{}` under the given ranges of columns, counted from 1 and inclusive
+    CLine(Vec<(usize, usize)>),
+    /// No rule
+    None,
+}
+
+impl Tex for Rule {
+    fn to_latex_string(&self) -> String {
+        match self {
+            Rule::HLine => r"\hline".to_string(),
+            Rule::CLine(ranges) => ranges
+                .iter()
+                .map(|(a, b)| format!(r"\cline{{{a}-{b}}}"))
+                .collect(),
+            Rule::None => String::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Row {
-    /// Elements inside of the row
-    elements: Vec<Element<Any>>,
+    /// Cells inside of the row
+    cells: Vec<Cell>,
+    /// The rule drawn above the row, `\hline` by default
+    rule: Rule,
 }
 
 impl Row {
     pub fn new(elements: Vec<Element<Any>>) -> Self {
-        Self { elements }
+        Self::from_cells(elements.into_iter().map(Cell::new).collect())
+    }
+    pub fn from_cells(cells: Vec<Cell>) -> Self {
+        Self {
+            cells,
+            rule: Rule::HLine,
+        }
+    }
+    /// Sets the rule drawn above the row
+    pub fn rule(mut self, rule: Rule) -> Self {
+        self.rule = rule;
+        self
     }
     pub fn to_element(&self) -> Element<Any> {
-        let text = Text::new(&self.to_latex_string(), TextType::Normal);
-        Element::from(text)
+        let text = Text::raw(&self.to_latex_string(), TextType::Normal);
+        Element::from(text).requiring(self.requirements())
+    }
+}
+
+/// The packages required by the elements of the cells
+impl Requirements for Row {
+    fn requirements(&self) -> Vec<Package> {
+        let elements = self.cells.iter().flat_map(|c| &c.element);
+        elements.flat_map(|e| e.requirements()).collect()
     }
 }
 
 impl Tex for Row {
     fn to_latex_string(&self) -> String {
-        let mut s = String::new();
-        for i in &self.elements {
-            s.push_str(&i.latex);
-            s.push_str(" & ")
-        }
-        let _ = s.remove(s.len() - 2);
-        s.push_str(r"\\");
-        s
+        let cells: Vec<String> = self.cells.iter().map(|c| c.to_latex_string()).collect();
+        format!(r"{} \\", cells.join(" & "))
     }
 }
 
@@ -209,3 +693,203 @@ pub fn vec_to_rows(vec: Vec<Vec<Element<Any>>>) -> Vec<Row> {
     }
     rows
 }
+
+/// How the values of a column built from data are typeset
+#[derive(Debug, Clone, Default)]
+pub struct ColumnFormat {
+    /// The alignment, by default right for numeric columns and left otherwise
+    pub align: Option<Position>,
+    /// The number of decimal places of numbers
+    pub precision: Option<usize>,
+    /// The latex placed between groups of thousands of numbers, like `,` or `\,`
+    pub thousands: Option<String>,
+}
+
+impl ColumnFormat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn align(mut self, align: Position) -> Self {
+        self.align = Some(align);
+        self
+    }
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+    pub fn thousands(mut self, separator: &str) -> Self {
+        self.thousands = Some(separator.to_string());
+        self
+    }
+    /// Typesets a value, formatting it if it is a number and escaping it otherwise
+    pub fn apply(&self, value: &str) -> String {
+        let number = value.trim();
+        if number.parse::<f64>().is_err() {
+            return escape(value);
+        }
+        let number = match self.precision {
+            Some(precision) => format!("{:.precision$}", number.parse::<f64>().unwrap_or(0.0)),
+            None => number.to_string(),
+        };
+        match &self.thousands {
+            Some(separator) => group_thousands(&number, separator),
+            None => escape(&number),
+        }
+    }
+}
+
+// inserts `separator` between groups of three digits of the integer part of `number`
+fn group_thousands(number: &str, separator: &str) -> String {
+    let (sign, unsigned) = match number.strip_prefix(['-', '+']) {
+        Some(rest) => (&number[..1], rest),
+        None => ("", number),
+    };
+    let (integer, fraction) = match unsigned.find(['.', 'e', 'E']) {
+        Some(i) => unsigned.split_at(i),
+        None => (unsigned, ""),
+    };
+    let digits: Vec<char> = integer.chars().collect();
+    let groups: Vec<String> = digits
+        .rchunks(3)
+        .rev()
+        .map(|group| group.iter().collect())
+        .collect();
+    format!("{sign}{}{fraction}", groups.join(separator))
+}
+
+impl Table {
+    /// Creates a table from a header and records of text, where each column is typeset
+    /// with the format at its index and the header is marked as such.
+    ///
+    /// Values are escaped, so they are typeset as they are.
+    /// The header and the records are padded with empty cells to the widest of them.
+    pub fn from_records(
+        header: Vec<String>,
+        records: Vec<Vec<String>>,
+        formats: &[ColumnFormat],
+    ) -> Self {
+        let count = records
+            .iter()
+            .map(|r| r.len())
+            .chain([header.len()])
+            .max()
+            .unwrap_or(0);
+        let default = ColumnFormat::default();
+        let format = |i: usize| formats.get(i).unwrap_or(&default);
+        let columns = (0..count)
+            .map(|i| {
+                let numeric = records
+                    .iter()
+                    .filter_map(|r| r.get(i))
+                    .all(|v| v.trim().is_empty() || v.trim().parse::<f64>().is_ok());
+                let align = match format(i).align {
+                    Some(align) => align,
+                    None if numeric && !records.is_empty() => Position::Right,
+                    None => Position::Left,
+                };
+                Column::new(align, Separator::Single)
+            })
+            .collect();
+        let cell = |latex: &str| Cell::new(Element::from(Text::raw(latex, TextType::Normal)));
+        let header_cells = (0..count)
+            .map(|i| match header.get(i) {
+                Some(name) => cell(&escape(name)),
+                None => Cell::empty(),
+            })
+            .collect();
+        let mut rows = vec![Row::from_cells(header_cells)];
+        for record in &records {
+            let cells = (0..count)
+                .map(|i| match record.get(i) {
+                    Some(value) => cell(&format(i).apply(value)),
+                    None => Cell::empty(),
+                })
+                .collect();
+            rows.push(Row::from_cells(cells))
+        }
+        Self::new(None, columns, rows, false, false).header(1)
+    }
+}
+
+feature! {
+    "csv"
+    impl Table {
+        /// Creates a table from a CSV file whose first record is the header,
+        /// formatting the columns like `from_records()`
+        pub fn from_csv(path: std::path::PathBuf, formats: &[ColumnFormat]) -> Result<Self, Error> {
+            let file = std::fs::File::open(path)?;
+            Self::from_csv_reader(file, formats)
+        }
+        /// Creates a table from CSV data whose first record is the header,
+        /// formatting the columns like `from_records()`
+        pub fn from_csv_reader<R: std::io::Read>(
+            reader: R,
+            formats: &[ColumnFormat],
+        ) -> Result<Self, Error> {
+            let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+            let header = reader.headers()?.iter().map(str::to_string).collect();
+            let mut records = Vec::new();
+            for record in reader.records() {
+                records.push(record?.iter().map(str::to_string).collect())
+            }
+            Ok(Self::from_records(header, records, formats))
+        }
+        /// Creates a table from serializable structs, with a header of their field names,
+        /// formatting the columns like `from_records()`
+        pub fn from_serialize<T: serde::Serialize>(
+            rows: impl IntoIterator<Item = T>,
+            formats: &[ColumnFormat],
+        ) -> Result<Self, Error> {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            let data = writer
+                .into_inner()
+                .map_err(|e| Error::Serialization(e.to_string()))?;
+            Self::from_csv_reader(data.as_slice(), formats)
+        }
+    }
+}
+
+/// A struct whose values are the rows of a table, usually implemented with `#[derive(TableRow)]`
+pub trait TableRow {
+    /// The text of the header of each column
+    fn headers() -> Vec<String>;
+    /// The alignment of each column
+    fn aligns() -> Vec<Position>;
+    /// The text of each cell of the value's row
+    fn cells(&self) -> Vec<String>;
+    /// The columns, separated by single lines
+    fn columns() -> Vec<Column> {
+        Self::aligns()
+            .into_iter()
+            .map(|align| Column::new(align, Separator::Single))
+            .collect()
+    }
+    /// The header row, with its text escaped
+    fn header() -> Row {
+        text_row(Self::headers())
+    }
+    /// The value's row, with its text escaped
+    fn row(&self) -> Row {
+        text_row(self.cells())
+    }
+}
+
+// a row of plain text cells
+fn text_row(cells: Vec<String>) -> Row {
+    let cells = cells
+        .iter()
+        .map(|c| Text::raw(&escape(c), TextType::Normal));
+    Row::from_cells(cells.map(|t| Cell::new(Element::from(t))).collect())
+}
+
+impl Table {
+    /// Creates a table with a header row followed by a row for each value
+    pub fn from_rows<T: TableRow>(rows: &[T]) -> Self {
+        let mut table_rows = vec![T::header()];
+        table_rows.extend(rows.iter().map(|r| r.row()));
+        Self::new(None, T::columns(), table_rows, false, false).header(1)
+    }
+}
@@ -0,0 +1,322 @@
+use crate::{escape_unless, Any, Element, Label, Level, Package, Requirements, Tex, Type};
+use serde::{Deserialize, Serialize};
+
+/// The styles of `amsthm`, set with `\theoremstyle{}`
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TheoremStyle {
+    /// Italic body, for theorems, lemmas, propositions and corollaries
+    Plain,
+    /// Upright body, for definitions and examples
+    Definition,
+    /// Upright body with an italic heading, for remarks
+    Remark,
+}
+
+impl Tex for TheoremStyle {
+    fn to_latex_string(&self) -> String {
+        match self {
+            TheoremStyle::Plain => "plain",
+            TheoremStyle::Definition => "definition",
+            TheoremStyle::Remark => "remark",
+        }
+        .to_string()
+    }
+}
+
+/// How the instances of a theorem kind are numbered
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Numbering {
+    /// With its own counter
+    Own,
+    /// With its own counter reset by another one, like `section` giving `2.1`
+    Within(String),
+    /// With the counter of another kind, like lemmas numbered along theorems
+    Shared(Box<TheoremKind>),
+    /// Without numbers, declared with `\newtheorem*`
+    Unnumbered,
+}
+
+/// A theorem-like environment declared with `\newtheorem`.
+///
+/// The declaration is added after the packages by every `Theorem` of this kind,
+/// so it can't drift out of sync with the environments used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TheoremKind {
+    /// The name of the environment, like `lemma`
+    pub name: String,
+    /// The heading of each instance, like `Lemma`
+    pub title: String,
+    pub style: TheoremStyle,
+    pub numbering: Numbering,
+}
+
+impl TheoremKind {
+    pub fn new(name: &str, title: &str, style: TheoremStyle) -> Self {
+        Self {
+            name: name.to_string(),
+            title: title.to_string(),
+            style,
+            numbering: Numbering::Own,
+        }
+    }
+    /// `theorem` in the plain style
+    pub fn theorem() -> Self {
+        Self::new("theorem", "Theorem", TheoremStyle::Plain)
+    }
+    /// `lemma` in the plain style
+    pub fn lemma() -> Self {
+        Self::new("lemma", "Lemma", TheoremStyle::Plain)
+    }
+    /// `proposition` in the plain style
+    pub fn proposition() -> Self {
+        Self::new("proposition", "Proposition", TheoremStyle::Plain)
+    }
+    /// `corollary` in the plain style
+    pub fn corollary() -> Self {
+        Self::new("corollary", "Corollary", TheoremStyle::Plain)
+    }
+    /// `definition` in the definition style
+    pub fn definition() -> Self {
+        Self::new("definition", "Definition", TheoremStyle::Definition)
+    }
+    /// `example` in the definition style
+    pub fn example() -> Self {
+        Self::new("example", "Example", TheoremStyle::Definition)
+    }
+    /// `remark` in the remark style
+    pub fn remark() -> Self {
+        Self::new("remark", "Remark", TheoremStyle::Remark)
+    }
+    /// Numbers the kind within a counter like `section` or `chapter`
+    pub fn within(mut self, counter: &str) -> Self {
+        self.numbering = Numbering::Within(counter.to_string());
+        self
+    }
+    /// Numbers the kind with the counter of `kind`
+    pub fn shared(mut self, kind: &TheoremKind) -> Self {
+        self.numbering = Numbering::Shared(Box::new(kind.clone()));
+        self
+    }
+    /// Leaves the kind unnumbered
+    pub fn unnumbered(mut self) -> Self {
+        self.numbering = Numbering::Unnumbered;
+        self
+    }
+    /// Returns the declarations the kind needs, where a shared counter's kind comes first
+    pub fn declarations(&self) -> Vec<String> {
+        let mut declarations = match &self.numbering {
+            Numbering::Shared(kind) => kind.declarations(),
+            _ => Vec::new(),
+        };
+        declarations.push(self.to_latex_string());
+        declarations
+    }
+}
+
+/// `\theoremstyle{}` followed by `\newtheorem{}`
+impl Tex for TheoremKind {
+    fn to_latex_string(&self) -> String {
+        let style = format!(r"\theoremstyle{{{}}}", self.style.to_latex_string());
+        let (name, title) = (&self.name, &self.title);
+        let declaration = match &self.numbering {
+            Numbering::Own => format!(r"\newtheorem{{{name}}}{{{title}}}"),
+            Numbering::Within(counter) => {
+                format!(r"\newtheorem{{{name}}}{{{title}}}[{counter}]")
+            }
+            Numbering::Shared(kind) => {
+                format!(r"\newtheorem{{{name}}}[{}]{{{title}}}", kind.name)
+            }
+            Numbering::Unnumbered => format!(r"\newtheorem*{{{name}}}{{{title}}}"),
+        };
+        format!("{style}\n{declaration}")
+    }
+}
+
+impl Requirements for TheoremKind {
+    fn requirements(&self) -> Vec<Package> {
+        vec![Package::new("amsthm")]
+    }
+}
+
+/// An instance of a theorem kind, with an optional title and label
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Theorem {
+    pub kind: TheoremKind,
+    pub title: Option<String>,
+    pub label: Option<Label>,
+    pub elements: Vec<Element<Any>>,
+    /// Inserts the title as trusted LaTeX instead of escaping it
+    #[serde(default)]
+    pub(crate) raw: bool,
+}
+
+impl Theorem {
+    pub fn new(kind: &TheoremKind) -> Self {
+        Self {
+            kind: kind.clone(),
+            title: None,
+            label: None,
+            elements: Vec::new(),
+            raw: false,
+        }
+    }
+    /// Sets the title shown in parentheses after the heading, escaping its LaTeX special
+    /// characters unless `raw()` is used
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+    /// Inserts the title as trusted LaTeX instead of escaping it
+    pub fn raw(mut self) -> Self {
+        self.raw = true;
+        self
+    }
+    /// Sets the label placed at the end of the environment
+    pub fn label(mut self, label: Label) -> Self {
+        self.label = Some(label);
+        self
+    }
+    pub fn push(&mut self, element: Element<Any>) {
+        self.elements.push(element)
+    }
+    pub fn push_array(&mut self, elements: Vec<Element<Any>>) {
+        self.elements.extend(elements)
+    }
+}
+
+impl Tex for Theorem {
+    fn to_latex_string(&self) -> String {
+        environment(
+            &self.kind.name,
+            self.title.as_deref(),
+            self.label.as_ref(),
+            &self.elements,
+            self.raw,
+        )
+    }
+}
+
+impl Requirements for Theorem {
+    fn requirements(&self) -> Vec<Package> {
+        self.kind.requirements()
+    }
+}
+
+/// Declares the kind of the theorem after the packages
+impl From<Theorem> for Element<Any> {
+    fn from(value: Theorem) -> Self {
+        let requires = value.requirements();
+        let declares = value.kind.declarations();
+        let latex = value.to_latex_string();
+        let label = value.label;
+        let any = Any {
+            value: value.kind.name,
+            latex,
+            type_: Type::T_Environment,
+            level: Level::Document,
+            header_level: None,
+            text_type: None,
+            list_type: None,
+            items: None,
+            elements: Some(value.elements),
+            package_options: None,
+            modified: false,
+        };
+        Element::new_any(any)
+            .requiring(requires)
+            .declaring(declares)
+            .labelled_at_last(label)
+    }
+}
+
+/// The `proof` environment of `amsthm`, ending with a QED symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct Proof {
+    /// Replaces the `Proof` heading, like `Proof of Theorem 2`
+    pub title: Option<String>,
+    pub elements: Vec<Element<Any>>,
+    /// Inserts the title as trusted LaTeX instead of escaping it
+    #[serde(default)]
+    pub(crate) raw: bool,
+}
+
+impl Proof {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets the heading used instead of `Proof`, escaping its LaTeX special characters
+    /// unless `raw()` is used
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+    /// Inserts the title as trusted LaTeX instead of escaping it
+    pub fn raw(mut self) -> Self {
+        self.raw = true;
+        self
+    }
+    pub fn push(&mut self, element: Element<Any>) {
+        self.elements.push(element)
+    }
+    pub fn push_array(&mut self, elements: Vec<Element<Any>>) {
+        self.elements.extend(elements)
+    }
+}
+
+impl Tex for Proof {
+    fn to_latex_string(&self) -> String {
+        environment(
+            "proof",
+            self.title.as_deref(),
+            None,
+            &self.elements,
+            self.raw,
+        )
+    }
+}
+
+impl Requirements for Proof {
+    fn requirements(&self) -> Vec<Package> {
+        vec![Package::new("amsthm")]
+    }
+}
+
+impl From<Proof> for Element<Any> {
+    fn from(value: Proof) -> Self {
+        let requires = value.requirements();
+        let latex = value.to_latex_string();
+        let any = Any {
+            value: "proof".to_string(),
+            latex,
+            type_: Type::T_Environment,
+            level: Level::Document,
+            header_level: None,
+            text_type: None,
+            list_type: None,
+            items: None,
+            elements: Some(value.elements),
+            package_options: None,
+            modified: false,
+        };
+        Element::new_any(any).requiring(requires)
+    }
+}
+
+// `\begin{name}[{title}]`, the elements and the label, then `\end{name}`,
+// where the braces keep a `]` in the title from ending the option
+fn environment(
+    name: &str,
+    title: Option<&str>,
+    label: Option<&Label>,
+    elements: &[Element<Any>],
+    raw: bool,
+) -> String {
+    let mut lines = vec![match title {
+        Some(title) => format!(r"\begin{{{name}}}[{{{}}}]", escape_unless(title, raw)),
+        None => format!(r"\begin{{{name}}}"),
+    }];
+    lines.extend(elements.iter().map(|e| e.value.to_latex_string()));
+    lines.extend(label.map(|l| l.to_latex_string()));
+    lines.push(format!(r"\end{{{name}}}"));
+    lines.join("\n")
+}
@@ -1,4 +1,5 @@
 use crate::feature;
+use crate::parse::find_commands;
 use crate::Level::*;
 use crate::TextType::*;
 use crate::Type::*;
@@ -7,7 +8,6 @@ use serde::{Deserialize, Serialize};
 use std::collections::linked_list::{Iter, IterMut};
 use std::collections::LinkedList;
 use std::fs::write;
-use std::io::Error;
 use std::path::PathBuf;
 use texcore_traits::*;
 feature! {
@@ -25,6 +25,12 @@ pub trait Tex {
     fn to_latex_string(&self) -> String;
 }
 
+/// Reports the packages an element needs in order to compile
+pub trait Requirements {
+    /// Returns the packages to load, which `ElementList` adds when they are missing
+    fn requirements(&self) -> Vec<Package>;
+}
+
 impl Tex for Any {
     fn to_latex_string(&self) -> String {
         self.latex.to_string()
@@ -35,7 +41,7 @@ impl Tex for Environment {
     fn to_latex_string(&self) -> String {
         let begin = format!(r"\begin{{{}}}", &self.name);
         let end = format!(r"\end{{{}}}", &self.name);
-        let strings = vec![begin, self.inner_latex_string(), end];
+        let strings = [begin, self.inner_latex_string(), end];
         strings.join("\n")
     }
 }
@@ -64,34 +70,49 @@ impl Tex for Input {
 
 impl Tex for Package {
     fn to_latex_string(&self) -> String {
-        format!(r"\usepackage{{{}}}", &self.pkg)
+        if self.options.is_empty() {
+            format!(r"\usepackage{{{}}}", &self.pkg)
+        } else {
+            let options: Vec<String> = self.options.iter().map(|o| o.to_latex_string()).collect();
+            format!(r"\usepackage[{}]{{{}}}", options.join(", "), &self.pkg)
+        }
+    }
+}
+
+impl Tex for PackageOption {
+    fn to_latex_string(&self) -> String {
+        match self {
+            PackageOption::Flag(flag) => flag.to_string(),
+            PackageOption::Value(key, value) => format!("{key}={value}"),
+        }
     }
 }
 
 impl Tex for Part {
     fn to_latex_string(&self) -> String {
-        format!(r"\part{{{}}}", &self.name)
+        format!(r"\part{{{}}}", escape_unless(&self.name, self.raw))
     }
 }
 
 impl Tex for Chapter {
     fn to_latex_string(&self) -> String {
-        format!(r"\chapter{{{}}}", &self.name)
+        format!(r"\chapter{{{}}}", escape_unless(&self.name, self.raw))
     }
 }
 
 impl Tex for Header {
     fn to_latex_string(&self) -> String {
+        let name = escape_unless(&self.name, self.raw);
         match self.header_level {
-            1 => format!(r"\section{{{}}}", &self.name),
+            1 => format!(r"\section{{{name}}}"),
             _ => {
                 let mut result = r"\".to_string();
-                let mut count = 0;
+                let mut count = 1;
                 while count < self.header_level {
                     result.push_str("sub");
                     count += 1;
                 }
-                result.push_str(&format!("section{{{}}}", &self.name));
+                result.push_str(&format!("section{{{name}}}"));
                 result
             }
         }
@@ -100,25 +121,26 @@ impl Tex for Header {
 
 impl Tex for Paragraph {
     fn to_latex_string(&self) -> String {
-        format!(r"\paragraph{{{}}}", &self.content)
+        format!(r"\paragraph{{{}}}", escape_unless(&self.content, self.raw))
     }
 }
 
 impl Tex for Text {
     fn to_latex_string(&self) -> String {
+        let content = escape_unless(&self.content, self.raw);
         match &self.type_ {
-            Bold => format!(r"\textbf{{{}}}", &self.content),
-            Italics => format!(r"\textit{{{}}}", &self.content),
-            Normal => self.content.to_string(),
+            Bold => format!(r"\textbf{{{content}}}"),
+            Italics => format!(r"\textit{{{content}}}"),
+            Normal => content,
             Math => format!("${}$", &self.content),
-            Par => format!(r"\par {{{}}}", &self.content),
+            Par => format!(r"\par {{{content}}}"),
         }
     }
 }
 
 impl Tex for Item {
     fn to_latex_string(&self) -> String {
-        format!(r"\item {{{}}}", &self.name)
+        format!(r"\item {{{}}}", escape_unless(&self.name, self.raw))
     }
 }
 
@@ -143,14 +165,26 @@ impl Tex for List {
 
 impl Tex for Metadata {
     fn to_latex_string(&self) -> String {
-        let doc_class = format!(
-            r"\documentclass[{}pt, {}]{{{}}}",
-            &self.fontsize, &self.papersize, &self.doc_class
-        );
-        let title = format!(r"\title{{{}}}", &self.title);
-        let author = format!(r"\author{{{}}}", &self.author);
-        let date = format!(r"\date{{{}}}", &self.date);
-        let result = vec![doc_class, title, author, date];
+        let options = self.class_options();
+        let mut result = Vec::new();
+        if options.is_empty() {
+            result.push(format!(r"\documentclass{{{}}}", &self.doc_class));
+        } else {
+            result.push(format!(
+                r"\documentclass[{}]{{{}}}",
+                options.join(", "),
+                &self.doc_class
+            ));
+        }
+        if let Some(title) = &self.title {
+            result.push(format!(r"\title{{{}}}", escape_unless(title, self.raw)));
+        }
+        if let Some(author) = &self.author {
+            result.push(format!(r"\author{{{}}}", escape_unless(author, self.raw)));
+        }
+        if let Some(date) = &self.date {
+            result.push(format!(r"\date{{{date}}}"));
+        }
         result.join("\n")
     }
 }
@@ -171,6 +205,7 @@ impl From<Part> for Element<Any> {
             list_type: None,
             items: None,
             elements: None,
+            package_options: None,
             latex,
             modified: value.modified,
         };
@@ -194,6 +229,7 @@ impl From<Chapter> for Element<Any> {
             list_type: None,
             items: None,
             elements: None,
+            package_options: None,
             latex,
             modified: value.modified,
         };
@@ -217,6 +253,7 @@ impl From<Header> for Element<Any> {
             list_type: None,
             items: None,
             elements: None,
+            package_options: None,
             latex,
             modified: value.modified,
         };
@@ -240,6 +277,7 @@ impl From<Paragraph> for Element<Any> {
             list_type: None,
             items: None,
             elements: None,
+            package_options: None,
             latex,
             modified: value.modified,
         };
@@ -263,6 +301,31 @@ impl From<Text> for Element<Any> {
             list_type: None,
             items: None,
             elements: None,
+            package_options: None,
+            latex,
+            modified: value.modified,
+        };
+        Element::new_any(any)
+    }
+}
+
+impl From<List> for Element<Any> {
+    fn from(value: List) -> Self {
+        let latex = if value.modified {
+            value.latex
+        } else {
+            value.to_latex_string()
+        };
+        let any = Any {
+            value: String::new(),
+            type_: T_List,
+            level: Document,
+            header_level: None,
+            text_type: None,
+            list_type: Some(value.type_),
+            items: Some(value.items),
+            elements: None,
+            package_options: None,
             latex,
             modified: value.modified,
         };
@@ -286,6 +349,7 @@ impl From<Package> for Element<Any> {
             list_type: None,
             items: None,
             elements: None,
+            package_options: Some(value.options),
             latex,
             modified: value.modified,
         };
@@ -309,6 +373,7 @@ impl From<Input> for Element<Any> {
             list_type: None,
             items: None,
             elements: None,
+            package_options: None,
             latex,
             modified: value.modified,
         };
@@ -318,6 +383,7 @@ impl From<Input> for Element<Any> {
 
 impl From<Environment> for Element<Any> {
     fn from(value: Environment) -> Self {
+        let requires = value.requirements();
         let latex = if value.modified {
             value.latex
         } else {
@@ -332,10 +398,11 @@ impl From<Environment> for Element<Any> {
             list_type: None,
             items: None,
             elements: Some(value.elements),
+            package_options: None,
             latex,
             modified: value.modified,
         };
-        Element::new_any(any)
+        Element::new_any(any).requiring(requires)
     }
 }
 
@@ -350,6 +417,7 @@ impl From<Custom> for Element<Any> {
             list_type: None,
             items: None,
             elements: None,
+            package_options: None,
             latex: value.latex,
             modified: value.modified,
         };
@@ -369,6 +437,7 @@ impl From<Comment> for Element<Any> {
             list_type: None,
             items: None,
             elements: None,
+            package_options: None,
             latex,
             modified: false,
         };
@@ -384,6 +453,18 @@ pub struct Element<T: Tex> {
     pub(crate) level: Level,
     pub(crate) latex: String,
     pub(crate) modified: bool,
+    #[serde(default)]
+    pub(crate) requires: Vec<Package>,
+    #[serde(default)]
+    pub(crate) label: Option<Label>,
+    /// Where the label starts in `latex`
+    #[serde(default)]
+    pub(crate) label_at: usize,
+    #[serde(default)]
+    pub(crate) declares: Vec<String>,
+    /// The problems found when the element was built, reported by `ElementList::validate()`
+    #[serde(default)]
+    pub(crate) problems: Vec<String>,
 }
 
 impl<T: Tex> Element<T> {
@@ -395,11 +476,64 @@ impl<T: Tex> Element<T> {
             level,
             latex,
             modified: false,
+            requires: Vec::new(),
+            label: None,
+            label_at: 0,
+            declares: Vec::new(),
+            problems: Vec::new(),
         }
     }
+    /// Declares a package the element needs
+    pub fn require(&mut self, package: Package) {
+        self.requires.push(package)
+    }
+    /// Returns the element with `requires` as its required packages
+    pub(crate) fn requiring(mut self, requires: Vec<Package>) -> Self {
+        self.requires = requires;
+        self
+    }
+    /// Returns the element with `declares` as the preamble lines it needs after the packages
+    pub(crate) fn declaring(mut self, declares: Vec<String>) -> Self {
+        self.declares = declares;
+        self
+    }
+    /// Returns the element with `problems` as the problems found building it
+    pub(crate) fn reporting(mut self, problems: Vec<String>) -> Self {
+        self.problems = problems;
+        self
+    }
+}
+
+/// The packages declared by the element and by all of its nested elements
+impl Requirements for Element<Any> {
+    fn requirements(&self) -> Vec<Package> {
+        let mut packages = self.requires.clone();
+        for e in self.value.elements.iter().flatten() {
+            packages.extend(e.requirements())
+        }
+        packages
+    }
 }
 
 impl Element<Any> {
+    /// Returns the preamble lines declared by the element and by all of its nested elements,
+    /// like the `\newtheorem` of a theorem
+    pub fn declarations(&self) -> Vec<String> {
+        let mut declarations = self.declares.clone();
+        for e in self.value.elements.iter().flatten() {
+            declarations.extend(e.declarations())
+        }
+        declarations
+    }
+    /// Returns the problems found when the element and its nested elements were built,
+    /// like a line with an alignment point in `gather`
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = self.problems.clone();
+        for e in self.value.elements.iter().flatten() {
+            problems.extend(e.problems())
+        }
+        problems
+    }
     /// Creates a new `Element<Any>`
     pub fn new_any(value: Any) -> Self {
         let type_ = value.type_;
@@ -412,6 +546,11 @@ impl Element<Any> {
             level,
             latex,
             modified,
+            requires: Vec::new(),
+            label: None,
+            label_at: 0,
+            declares: Vec::new(),
+            problems: Vec::new(),
         }
     }
 }
@@ -430,6 +569,8 @@ impl ExtraOptions for Element<Any> {
 pub struct ElementList<T: Tex> {
     metadata: Metadata,
     list: LinkedList<Element<T>>,
+    #[serde(default)]
+    bibliography: Option<Bibliography>,
 }
 
 impl ElementList<Any> {
@@ -438,6 +579,7 @@ impl ElementList<Any> {
         Self {
             metadata: metadata.to_owned(),
             list: LinkedList::new(),
+            bibliography: None,
         }
     }
     /// A forward iterator of elements in the list
@@ -461,7 +603,7 @@ impl ElementList<Any> {
     }
     /// Adds in `\newpage` text as next element in the list
     pub fn add_newpage(&mut self) {
-        let text = Text::new(r"\newpage", Normal);
+        let text = Text::raw(r"\newpage", Normal);
         self.push(Element::from(text));
     }
     /// Pushes an element to the end of the list
@@ -497,11 +639,11 @@ impl ElementList<Any> {
         if self.metadata.maketitle {
             document.push(r"\maketitle".to_owned());
         }
-        for i in self.iter() {
-            iter_push(i, &mut document, &mut packages, &mut meta)
+        for i in self.iter_merged() {
+            iter_push(&i, &mut document, &mut packages, &mut meta)
         }
         document.push(r"\end{document}".to_owned());
-        let result = vec![meta.join("\n"), packages.join("\n"), document.join("\n")];
+        let result = [meta.join("\n"), packages.join("\n"), document.join("\n")];
         result.join("\n")
     }
     /// Walks the list and returns a split latex string separating Packages level
@@ -515,13 +657,119 @@ impl ElementList<Any> {
         if self.metadata.maketitle {
             document.push(r"\maketitle".to_owned());
         }
-        for i in self.iter() {
-            iter_push(i, &mut document, &mut packages, &mut meta)
+        for i in self.iter_merged() {
+            iter_push(&i, &mut document, &mut packages, &mut meta)
         }
         document.push(r"\end{document}".to_owned());
-        let result = vec![meta.join("\n"), document.join("\n")];
+        let result = [meta.join("\n"), document.join("\n")];
         (result.join("\n"), packages.join("\n"))
     }
+    /// Returns the `Packages` level elements along with the packages required by the
+    /// elements that are missing, with duplicate packages merged into one,
+    /// unioning their options, and `hyperref` moved after the other packages but
+    /// before the ones that must be loaded after it (like `cleveref`).
+    ///
+    /// Elements that follow a package (like `\hypersetup{}`) are moved along with it.
+    pub fn packages(&self) -> Vec<Element<Any>> {
+        self.merge_packages().0
+    }
+    /// Returns a warning for every package option set to different values
+    /// across duplicate packages and for every environment declared in different ways,
+    /// like a theorem kind numbered differently, in which case the first one is kept
+    pub fn package_conflicts(&self) -> Vec<String> {
+        self.merge_packages().1
+    }
+    // the elements of the list with the `Packages` level replaced by `packages()`
+    pub(crate) fn iter_merged(&self) -> Vec<Element<Any>> {
+        let mut vec: Vec<Element<Any>> = self
+            .iter()
+            .filter(|e| e.level != Packages)
+            .cloned()
+            .collect();
+        vec.extend(self.packages());
+        vec
+    }
+    fn merge_packages(&self) -> (Vec<Element<Any>>, Vec<String>) {
+        let mut merged: Vec<(usize, Element<Any>)> = Vec::new();
+        let mut conflicts = Vec::new();
+        let mut rank = 0;
+        let mut elements: Vec<Element<Any>> = self
+            .iter()
+            .filter(|e| e.level == Packages)
+            .cloned()
+            .collect();
+        for e in self.iter() {
+            elements.extend(e.requirements().into_iter().map(Element::from))
+        }
+        for element in &elements {
+            // modified packages can't be merged but are still ordered like the others
+            if element.type_ == T_Package {
+                rank = package_rank(&element.value.value);
+            }
+            if !is_package(element) {
+                merged.push((rank, element.clone()));
+                continue;
+            }
+            let name = &element.value.value;
+            let options = element.value.package_options.clone().unwrap_or_default();
+            let existing = merged
+                .iter_mut()
+                .find(|(_, e)| is_package(e) && &e.value.value == name);
+            match existing {
+                Some((_, e)) => {
+                    let mut package = Package::with_options(
+                        name,
+                        e.value.package_options.clone().unwrap_or_default(),
+                    );
+                    for option in options {
+                        let same_key = package.options.iter().find(|o| o.key() == option.key());
+                        match same_key {
+                            Some(o) if *o == option => {}
+                            Some(o) => conflicts.push(format!(
+                                "package `{name}`: option `{}` conflicts with `{}`",
+                                option.to_latex_string(),
+                                o.to_latex_string()
+                            )),
+                            None => package.push_option(option),
+                        }
+                    }
+                    *e = Element::from(package);
+                }
+                None => merged.push((rank, Element::from(Package::with_options(name, options)))),
+            }
+        }
+        // declarations come after every package, skipping the environments already declared
+        let mut environments: Vec<(&str, String)> = Vec::new();
+        for e in &elements {
+            environments.extend(declared_environments(&e.latex).map(|n| (n, e.latex.clone())))
+        }
+        let declarations: Vec<String> = self.iter().flat_map(|e| e.declarations()).collect();
+        let mut declared: Vec<String> = Vec::new();
+        for declaration in &declarations {
+            let names: Vec<&str> = declared_environments(declaration).collect();
+            let earlier = environments.iter().find(|(n, _)| names.contains(n));
+            match earlier {
+                Some((_, d)) if d == declaration => {}
+                Some((name, d)) => conflicts.push(format!(
+                    "environment `{name}`: declaration `{declaration}` conflicts with `{d}`"
+                )),
+                None if declared.contains(declaration) => {}
+                None => {
+                    environments.extend(names.iter().map(|n| (*n, declaration.clone())));
+                    declared.push(declaration.clone())
+                }
+            }
+        }
+        for declaration in declared {
+            merged.push((
+                usize::MAX,
+                Element::from(Custom::new(&declaration, Packages)),
+            ))
+        }
+        // a stable sort keeps the original order within each rank
+        merged.sort_by_key(|(rank, _)| *rank);
+        (merged.into_iter().map(|(_, e)| e).collect(), conflicts)
+    }
     /// Writes `ElementList` into a latex file
     pub fn write(&self, main: PathBuf) -> Result<(), Error> {
         let latex = self.to_latex_string();
@@ -530,33 +778,46 @@ impl ElementList<Any> {
     }
     /// Writes `ElementList` into two latex files splitting the `main` content and `path` for packages
     /// Input is used to declare the appropriate `\input{}` for your package file
-    pub fn write_split(&self, main: PathBuf, structure: PathBuf, input: Input) {
+    pub fn write_split(
+        &self,
+        main: PathBuf,
+        structure: PathBuf,
+        input: Input,
+    ) -> Result<(), Error> {
+        use std::panic::resume_unwind;
         let (main_tex, str_tex) = self.to_latex_split_string(input);
         std::thread::scope(|s| {
-            s.spawn(move || {
-                write_file(main, main_tex.as_bytes()).expect("Couldn't write main file")
-            });
-            s.spawn(move || {
-                write_file(structure, str_tex.as_bytes()).expect("Couldn't write structure file")
-            });
+            let m = s.spawn(move || write_file(main, main_tex.as_bytes()));
+            let st = s.spawn(move || write_file(structure, str_tex.as_bytes()));
+            let m = m.join().unwrap_or_else(|e| resume_unwind(e));
+            let st = st.join().unwrap_or_else(|e| resume_unwind(e));
+            m.and(st)
         })
     }
+    /// Writes the attached bibliography into a `.bib` file
+    pub fn write_bib(&self, bib: PathBuf) -> Result<(), Error> {
+        match &self.bibliography {
+            Some(bibliography) => bibliography.write(bib),
+            None => Err(Error::Validation("no bibliography is attached".to_string())),
+        }
+    }
     feature! {
        "parallel"
         /// A parallel alternate to `write()`
-        pub fn par_write(&self, main: PathBuf){
-            let pool = ThreadPoolBuilder::default().build().expect("Couldn't build pool");
+        pub fn par_write(&self, main: PathBuf) -> Result<(), Error> {
+            let pool = build_pool()?;
             let latex = pool.install(|| self.to_latex_string());
-            pool.install(|| write_file(main, latex.as_bytes()).expect("Couldn't write latex file in pool"));
+            pool.install(|| write_file(main, latex.as_bytes()))
         }
         /// A parallel alternate to `write_split()`
-        pub fn par_write_split(&self, main: PathBuf, structure: PathBuf, input: Input){
-            let pool = ThreadPoolBuilder::default().build().expect("Couldn't build pool");
+        pub fn par_write_split(&self, main: PathBuf, structure: PathBuf, input: Input) -> Result<(), Error> {
+            let pool = build_pool()?;
             let (main_tex, str_tex) = pool.install(|| self.to_latex_split_string(input));
-            pool.join(
-                || write_file(main, main_tex.as_bytes()).expect("Couldn't write main file in pool"),
-                || write_file(structure, str_tex.as_bytes()).expect("Couldn't write structure file in pool")
+            let (m, st) = pool.join(
+                || write_file(main, main_tex.as_bytes()),
+                || write_file(structure, str_tex.as_bytes())
             );
+            m.and(st)
         }
         pub fn par_iter(&self) -> impl ParallelIterator<Item=&Element<Any>> {
             use rayon::prelude::*;
@@ -565,12 +826,26 @@ impl ElementList<Any> {
     }
 
     #[cfg(feature = "compile")]
-    /// Compiles the list into a pdf file
+    /// Compiles the list into a pdf file, failing early if `check()` finds any problem
+    /// or `check_assets()` finds missing images in the working directory,
+    /// which is where tectonic resolves included files whatever `path` is
     pub fn compile(&self, path: PathBuf) -> Result<(), Error> {
+        use crate::bundle::graphicx::Engine;
         use std::fs::File;
+        use std::io::Write;
+        use std::path::Path;
+        self.check()?;
+        let assets: Vec<String> = self
+            .check_assets(Path::new("."), Engine::Tectonic, None)
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        if !assets.is_empty() {
+            return Err(Error::Validation(assets.join("\n")));
+        }
         let mut file = File::create(path)?;
         let latex = self.to_latex_string();
-        let pdf = latex_to_pdf(latex)?;
+        let pdf = latex_to_pdf(latex).map_err(|e| Error::Compile(e.to_string()))?;
         file.write_all(&pdf)?;
         Ok(())
     }
@@ -590,6 +865,14 @@ impl ElementList<Any> {
     pub fn metadata(&self) -> Metadata {
         self.metadata.clone()
     }
+    /// Attaches the database that citations are checked against
+    pub fn set_bibliography(&mut self, bibliography: Bibliography) {
+        self.bibliography = Some(bibliography)
+    }
+    /// returns the attached bibliography
+    pub fn bibliography(&self) -> Option<&Bibliography> {
+        self.bibliography.as_ref()
+    }
     /// returns a reference to the inner linked list
     pub fn list(&self) -> &LinkedList<Element<Any>> {
         &self.list
@@ -601,10 +884,45 @@ impl Default for ElementList<Any> {
         Self {
             metadata: Metadata::default(),
             list: LinkedList::new(),
+            bibliography: None,
         }
     }
 }
 
+feature! {
+    "parallel"
+    // A helper function to build a thread pool
+    fn build_pool() -> Result<ThreadPool, Error> {
+        ThreadPoolBuilder::default()
+            .build()
+            .map_err(|e| Error::Io(std::io::Error::other(e)))
+    }
+}
+
+// Packages loaded at the end, in this order
+const LATE_PACKAGES: [&str; 4] = ["hyperref", "bookmark", "hypcap", "cleveref"];
+
+// The rank of a package in the load order, where `0` is for any package that isn't late
+fn package_rank(name: &str) -> usize {
+    LATE_PACKAGES
+        .iter()
+        .position(|p| *p == name)
+        .map_or(0, |i| i + 1)
+}
+
+// The environments declared in the latex, like `lemma` for `\newtheorem{lemma}{Lemma}`
+fn declared_environments(latex: &str) -> impl Iterator<Item = &str> {
+    let names = ["newtheorem", "newtheorem*", "newenvironment"];
+    find_commands(latex, &names)
+        .into_iter()
+        .map(|(_, n)| n.trim())
+}
+
+// Whether an element is an unmodified `Package` that can be merged
+fn is_package(element: &Element<Any>) -> bool {
+    element.type_ == T_Package && !element.modified
+}
+
 // A helper function to write bytes to a file
 fn write_file(path: PathBuf, bytes: &[u8]) -> Result<(), Error> {
     write(path, bytes)?;
@@ -0,0 +1,62 @@
+use crate::feature;
+use std::fmt::{Display, Formatter};
+
+/// The errors returned by the fallible operations of TexCore
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file failed
+    Io(std::io::Error),
+    /// A value couldn't be serialized or deserialized
+    Serialization(String),
+    /// A version isn't in the `major.minor.patch` format
+    VersionParse(String),
+    /// The LaTeX source couldn't be compiled into a pdf
+    Compile(String),
+    /// A document or an element is invalid
+    Validation(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {e}"),
+            Error::Serialization(e) => write!(f, "serialization error: {e}"),
+            Error::VersionParse(e) => write!(f, "invalid version: {e}"),
+            Error::Compile(e) => write!(f, "compile error: {e}"),
+            Error::Validation(e) => write!(f, "validation error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Error::Io(value)
+    }
+}
+
+feature! {
+    "texcreate_template"
+    impl From<serde_json::Error> for Error {
+        fn from(value: serde_json::Error) -> Self {
+            Error::Serialization(value.to_string())
+        }
+    }
+}
+
+feature! {
+    "csv"
+    impl From<csv::Error> for Error {
+        fn from(value: csv::Error) -> Self {
+            Error::Serialization(value.to_string())
+        }
+    }
+}
@@ -0,0 +1,26 @@
+/// Escapes the LaTeX special characters (`& % $ # _ { } ~ ^ \`) in `text` so it is typeset verbatim
+pub fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                result.push('\\');
+                result.push(c)
+            }
+            '~' => result.push_str(r"\textasciitilde{}"),
+            '^' => result.push_str(r"\textasciicircum{}"),
+            '\\' => result.push_str(r"\textbackslash{}"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Escapes `text` unless `raw` is set, in which case it is trusted LaTeX and returned as is
+pub(crate) fn escape_unless(text: &str, raw: bool) -> String {
+    if raw {
+        text.to_string()
+    } else {
+        escape(text)
+    }
+}
@@ -2,13 +2,14 @@ use futures::future::ready;
 use futures::Future;
 use std::path::PathBuf;
 use tokio::fs::File;
-use tokio::io::{AsyncWriteExt, Result};
+use tokio::io::AsyncWriteExt;
+use tokio::task::JoinError;
 use tokio::{join, spawn};
 
 use crate::Element;
 use crate::ElementList;
 use crate::Level::*;
-use crate::{Any, Input, Tex};
+use crate::{Any, Error, Input, Tex};
 
 /// A type to provide asynchronous support to TeX elements
 ///
@@ -66,12 +67,12 @@ impl ElementList<Any> {
             if list.metadata().maketitle {
                 document.push(r"\maketitle".to_owned());
             }
-            for i in list.iter() {
-                iter_push(i, &mut document, &mut packages, &mut meta).await
+            for i in list.iter_merged() {
+                iter_push(&i, &mut document, &mut packages, &mut meta).await
             }
 
             document.push(r"\end{document}".to_owned());
-            let result = vec![meta.join("\n"), packages.join("\n"), document.join("\n")];
+            let result = [meta.join("\n"), packages.join("\n"), document.join("\n")];
             result.join("\n")
         })
         .await
@@ -89,12 +90,12 @@ impl ElementList<Any> {
             if list.metadata().maketitle {
                 document.push(r"\maketitle".to_owned());
             }
-            for i in list.iter() {
-                iter_push(i, &mut document, &mut packages, &mut meta).await
+            for i in list.iter_merged() {
+                iter_push(&i, &mut document, &mut packages, &mut meta).await
             }
 
             document.push(r"\end{document}".to_owned());
-            let result = vec![meta.join("\n"), document.join("\n")];
+            let result = [meta.join("\n"), document.join("\n")];
             (result.join("\n"), packages.join("\n"))
         })
         .await
@@ -103,16 +104,11 @@ impl ElementList<Any> {
     /// Asynchronously version of `write()`
     ///
     /// Writes a file in a separate thread
-    pub async fn async_write(&self, main: PathBuf) -> Result<()> {
+    pub async fn async_write(&self, main: PathBuf) -> Result<(), Error> {
         let s = self.async_latex_string().await;
-        spawn(async move {
-            write_file(main, s.as_bytes())
-                .await
-                .expect("Couldn't write to file");
-        })
-        .await
-        .unwrap();
-        Ok(())
+        spawn(async move { write_file(main, s.as_bytes()).await })
+            .await
+            .map_err(join_error)?
     }
     /// Asynchronous version of `write_split()`
     ///
@@ -122,21 +118,26 @@ impl ElementList<Any> {
         main: PathBuf,
         structure: PathBuf,
         input: Input,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         let (main_data, str_data) = self.async_latex_split_string(input).await;
         let task_m = spawn(async move { write_file(main, main_data.as_bytes()).await });
 
         let task_s = spawn(async move { write_file(structure, str_data.as_bytes()).await });
         let (r1, r2) = join!(task_m, task_s);
         {
-            r1??;
-            r2??;
+            r1.map_err(join_error)??;
+            r2.map_err(join_error)??;
         }
         Ok(())
     }
 }
 
-async fn write_file(path: PathBuf, bytes: &[u8]) -> Result<()> {
+// A failed task is reported as an I/O error
+fn join_error(e: JoinError) -> Error {
+    Error::Io(std::io::Error::other(e))
+}
+
+async fn write_file(path: PathBuf, bytes: &[u8]) -> Result<(), Error> {
     let mut file = File::create(path).await?;
     file.write_all(bytes).await?;
     Ok(())
@@ -1,11 +1,17 @@
-#![feature(doc_auto_cfg)]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 //! # Examples
 //! ```rust
 #![doc = include_str ! ("../examples/doc_example.rs")]
 //! ```
+// lets derived code refer to `texcore::` from within the crate too
+extern crate self as texcore;
+
 /// Element controls everything related to `Elements` and `ElementList`
 pub mod element;
+/// The crate-wide `Error` type
+pub mod error;
+/// Escaping of LaTeX special characters in plain text
+pub mod escape;
 #[cfg(feature = "async")]
 /// Provides asynchronous methods using the type `TexAsync`
 pub mod future;
@@ -20,31 +26,49 @@ pub mod level;
 #[allow(unused_macros)]
 #[macro_use]
 pub(crate) mod macros;
+/// Provides BibTeX databases, citations and bibliographies
+pub mod bibliography;
 /// Provide bundles of `Element`s for different common use cases
 pub mod bundle;
+/// Reads existing LaTeX source code back into an `ElementList`
+pub mod parse;
+/// Provides labels and references to them
+pub mod reference;
 #[cfg(feature = "texcreate_template")]
 /// Provides the `Template` type for the TexCreate project
 pub mod template;
+/// Provides `DocumentTree`, a hierarchical alternative to `ElementList` where
+/// parts, chapters and sections own their body elements
+pub mod tree;
 /// Type controls the different kinds of latex elements
 pub mod ty;
+/// Checks a document for problems before it is compiled
+pub mod validate;
 // Testing all in a single module
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests;
 
+pub use bibliography::*;
 pub use element::*;
+pub use error::*;
+pub use escape::*;
 pub use level::*;
+pub use reference::*;
+pub use tree::*;
 pub use ty::*;
+pub use validate::*;
 
 feature! {
     "compile"
-    use std::io::{Error, Write};
+    use std::io::Write;
     use std::path::PathBuf;
     use tectonic::latex_to_pdf;
 
     /// Compiles a tex file to a pdf file
     pub fn compile(path: PathBuf, output_path: PathBuf) -> Result<(), Error> {
         let source = std::fs::read_to_string(path)?;
-        let pdf = latex_to_pdf(source)?;
+        let pdf = latex_to_pdf(source).map_err(|e| Error::Compile(e.to_string()))?;
         let mut output = std::fs::File::create(output_path)?;
         output.write_all(&pdf)?;
         Ok(())
@@ -0,0 +1,542 @@
+use crate::Level::*;
+use crate::*;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+use texcore_traits::{ExtraOptions, Options};
+
+// environments whose content must never be interpreted
+const VERBATIM: [&str; 5] = ["verbatim", "verbatim*", "lstlisting", "minted", "comment"];
+
+impl ElementList<Any> {
+    /// Parses LaTeX source code into a list.
+    ///
+    /// - `\documentclass`, `\title`, `\author` and `\date` are read into the `Metadata`
+    /// - `\usepackage{}` becomes a `Package` per name
+    /// - `\part`, `\chapter`, `\section` to `\subsubsection`, `\paragraph`, environments,
+    ///   lists, comments and `\input` become their respective elements
+    /// - Prose becomes `TextType::Normal` text and anything else is kept as `Custom`
+    ///
+    /// All text is kept as trusted LaTeX, so it isn't escaped a second time.
+    pub fn from_latex(source: &str) -> Self {
+        let (preamble, body) = match find_env(source, "document") {
+            Some((start, inner_start, inner_end, _)) => {
+                (&source[..start], &source[inner_start..inner_end])
+            }
+            None => ("", source),
+        };
+        let mut metadata = Metadata::builder().raw(true).build();
+        let packages = parse_preamble(preamble, &mut metadata);
+        let (document, maketitle) = parse_body(body);
+        metadata.maketitle = maketitle;
+        let mut list = ElementList::new(&metadata);
+        list.push_array(packages);
+        list.push_array(document);
+        list
+    }
+    /// Reads a `.tex` file and parses it using `from_latex()`
+    pub fn from_file(path: PathBuf) -> Result<Self, Error> {
+        let source = read_to_string(path)?;
+        Ok(Self::from_latex(&source))
+    }
+}
+
+fn parse_preamble(s: &str, metadata: &mut Metadata) -> Vec<Element<Any>> {
+    let mut elements = Vec::new();
+    let mut run = 0;
+    let mut i = 0;
+    while i < s.len() {
+        match s.as_bytes()[i] {
+            b'\\' => {
+                let (name, after) = read_control(s, i);
+                let (args, end) = read_args(s, after);
+                let handled = match (name, args.as_slice()) {
+                    ("documentclass", [.., Arg::Curly(class)]) => {
+                        if let [Arg::Square(options), ..] = args.as_slice() {
+                            for option in options.split(',').map(str::trim) {
+                                match option.strip_suffix("pt").map(str::parse) {
+                                    Some(Ok(size)) => metadata.fontsize = Some(size),
+                                    _ if option.ends_with("paper") => {
+                                        metadata.papersize = Some(option.to_string())
+                                    }
+                                    _ if option.is_empty() => {}
+                                    _ => metadata.options.push(ClassOption::from(option)),
+                                }
+                            }
+                        }
+                        metadata.doc_class = class.trim().to_string();
+                        Some(Vec::new())
+                    }
+                    ("title", [Arg::Curly(v)]) => {
+                        metadata.title = Some(v.trim().to_string());
+                        Some(Vec::new())
+                    }
+                    ("author", [Arg::Curly(v)]) => {
+                        metadata.author = Some(v.trim().to_string());
+                        Some(Vec::new())
+                    }
+                    ("date", [Arg::Curly(v)]) => {
+                        metadata.date = Some(v.trim().to_string());
+                        Some(Vec::new())
+                    }
+                    ("usepackage", [Arg::Curly(pkgs)]) => Some(packages(pkgs, "")),
+                    ("usepackage", [Arg::Square(options), Arg::Curly(pkgs)]) => {
+                        Some(packages(pkgs, options))
+                    }
+                    ("input", [Arg::Curly(file)]) => {
+                        let input = Input::new(PathBuf::from(file.trim()), Packages);
+                        Some(vec![Element::from(input)])
+                    }
+                    _ => None,
+                };
+                match handled {
+                    Some(found) => {
+                        push_custom(&s[run..i], Packages, &mut elements);
+                        elements.extend(found);
+                        i = end;
+                        run = end;
+                    }
+                    None => i = after,
+                }
+            }
+            b'%' if starts_line(s, i) => {
+                let end = line_end(s, i);
+                push_custom(&s[run..i], Packages, &mut elements);
+                elements.push(comment(&s[i..end], Packages));
+                i = end;
+                run = end;
+            }
+            b'%' => i = line_end(s, i),
+            _ => i += 1,
+        }
+    }
+    push_custom(&s[run..], Packages, &mut elements);
+    elements
+}
+
+// a `Package` for each comma separated name, all loaded with the same options
+fn packages(names: &str, options: &str) -> Vec<Element<Any>> {
+    let options: Vec<PackageOption> = options
+        .split(',')
+        .map(str::trim)
+        .filter(|o| !o.is_empty())
+        .map(PackageOption::from)
+        .collect();
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| Element::from(Package::with_options(p, options.clone())))
+        .collect()
+}
+
+// returns the elements of a document body and whether it contains `\maketitle`
+fn parse_body(s: &str) -> (Vec<Element<Any>>, bool) {
+    let mut elements = Vec::new();
+    let mut maketitle = false;
+    let mut run = 0;
+    let mut i = 0;
+    while i < s.len() {
+        match s.as_bytes()[i] {
+            b'\\' => {
+                let (name, after) = read_control(s, i);
+                let found = match name {
+                    "part" | "chapter" | "section" | "subsection" | "subsubsection"
+                    | "paragraph" => match read_args(s, after) {
+                        (args, end) if matches!(args.as_slice(), [Arg::Curly(_)]) => {
+                            let title = args[0].inner().trim();
+                            let element = match name {
+                                "part" => Element::from(Part::raw(title)),
+                                "chapter" => Element::from(Chapter::raw(title)),
+                                "section" => Element::from(Header::raw(title, 1)),
+                                "subsection" => Element::from(Header::raw(title, 2)),
+                                "subsubsection" => Element::from(Header::raw(title, 3)),
+                                _ => Element::from(Paragraph::raw(title)),
+                            };
+                            Some((vec![element], end))
+                        }
+                        _ => None,
+                    },
+                    "input" => match read_args(s, after) {
+                        (args, end) if matches!(args.as_slice(), [Arg::Curly(_)]) => {
+                            let file = PathBuf::from(args[0].inner().trim());
+                            Some((vec![Element::from(Input::new(file, Document))], end))
+                        }
+                        _ => None,
+                    },
+                    "maketitle" => {
+                        maketitle = true;
+                        Some((Vec::new(), after))
+                    }
+                    "begin" => parse_env(s, i),
+                    _ => None,
+                };
+                match found {
+                    Some((found, end)) => {
+                        push_text(&s[run..i], &mut elements);
+                        elements.extend(found);
+                        i = end;
+                        run = end;
+                    }
+                    None => i = after,
+                }
+            }
+            b'%' if starts_line(s, i) => {
+                let end = line_end(s, i);
+                push_text(&s[run..i], &mut elements);
+                elements.push(comment(&s[i..end], Document));
+                i = end;
+                run = end;
+            }
+            b'%' => i = line_end(s, i),
+            _ => i += 1,
+        }
+    }
+    push_text(&s[run..], &mut elements);
+    (elements, maketitle)
+}
+
+// parses the environment starting at `i`, returning its element and where it ends
+fn parse_env(s: &str, i: usize) -> Option<(Vec<Element<Any>>, usize)> {
+    let (_, after) = read_control(s, i);
+    let (name, _) = read_group(s, after, b'{', b'}')?;
+    let (start, inner_start, inner_end, end) = find_env(&s[i..], name)?;
+    debug_assert_eq!(start, 0);
+    let block = &s[i..i + end];
+    let inner = &s[i + inner_start..i + inner_end];
+    if VERBATIM.contains(&name) {
+        return Some((vec![Element::from(Custom::new(block, Document))], i + end));
+    }
+    // arguments directly following `\begin{name}` are kept as options
+    let (args, args_end) = read_args(inner, 0);
+    let inner = &inner[args_end..];
+    let element = match name {
+        "itemize" | "enumerate" if args.is_empty() => {
+            let type_ = if name == "itemize" {
+                ListType::Itemized
+            } else {
+                ListType::Enumerated
+            };
+            match parse_items(inner) {
+                Some(items) => Element::from(List::new(type_, items)),
+                None => Element::from(Custom::new(block, Document)),
+            }
+        }
+        _ => {
+            let mut env = Environment::new(name);
+            let (elements, _) = parse_body(inner);
+            env.set_elements(elements);
+            if !args.is_empty() {
+                env.modify_element(args.iter().map(Arg::to_option).collect());
+            }
+            Element::from(env)
+        }
+    };
+    Some((vec![element], i + end))
+}
+
+// splits the content of a list at each top level `\item`, giving up on labelled items
+fn parse_items(s: &str) -> Option<Vec<Item>> {
+    let mut starts = Vec::new();
+    let mut i = 0;
+    while i < s.len() {
+        match s.as_bytes()[i] {
+            b'\\' => {
+                let (name, after) = read_control(s, i);
+                match name {
+                    "item" => {
+                        starts.push((i, after));
+                        i = after
+                    }
+                    "begin" => i = parse_env_end(s, i).unwrap_or(after),
+                    _ => i = after,
+                }
+            }
+            b'{' => i = read_group(s, i, b'{', b'}').map_or(i + 1, |(_, end)| end),
+            b'%' => i = line_end(s, i),
+            _ => i += 1,
+        }
+    }
+    let first = starts.first().map_or(s.len(), |(start, _)| *start);
+    if !s[..first].trim().is_empty() {
+        return None;
+    }
+    let mut items = Vec::new();
+    for (n, (_, after)) in starts.iter().enumerate() {
+        let end = starts.get(n + 1).map_or(s.len(), |(start, _)| *start);
+        let content = s[*after..end].trim();
+        if content.starts_with('[') {
+            return None;
+        }
+        let content = match read_group(content, 0, b'{', b'}') {
+            Some((inner, end)) if end == content.len() => inner.trim(),
+            _ => content,
+        };
+        items.push(Item::raw(content))
+    }
+    Some(items)
+}
+
+fn parse_env_end(s: &str, i: usize) -> Option<usize> {
+    let (_, after) = read_control(s, i);
+    let (name, _) = read_group(s, after, b'{', b'}')?;
+    let (_, _, _, end) = find_env(&s[i..], name)?;
+    Some(i + end)
+}
+
+// Pushes prose split into paragraphs, where a paragraph made of a single command becomes `Custom`
+fn push_text(s: &str, elements: &mut Vec<Element<Any>>) {
+    let mut prose: Vec<&str> = Vec::new();
+    for paragraph in paragraphs(s) {
+        if is_command(paragraph) {
+            flush_prose(&mut prose, elements);
+            elements.push(Element::from(Custom::new(paragraph, Document)));
+        } else {
+            prose.push(paragraph);
+        }
+    }
+    flush_prose(&mut prose, elements);
+}
+
+fn flush_prose(prose: &mut Vec<&str>, elements: &mut Vec<Element<Any>>) {
+    if !prose.is_empty() {
+        let text = Text::raw(&prose.join("\n\n"), TextType::Normal);
+        elements.push(Element::from(text));
+        prose.clear();
+    }
+}
+
+fn push_custom(s: &str, level: Level, elements: &mut Vec<Element<Any>>) {
+    let s = s.trim();
+    if !s.is_empty() {
+        elements.push(Element::from(Custom::new(s, level)))
+    }
+}
+
+fn comment(line: &str, level: Level) -> Element<Any> {
+    let value = line.trim_start().trim_start_matches('%').trim();
+    Element::from(Comment::new(value, level))
+}
+
+// splits text on blank lines, dropping empty paragraphs
+fn paragraphs(s: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    for line in s.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            push_trimmed(&s[start..offset], &mut result);
+            start = offset + line.len();
+        }
+        offset += line.len();
+    }
+    push_trimmed(&s[start..], &mut result);
+    result
+}
+
+fn push_trimmed<'a>(s: &'a str, result: &mut Vec<&'a str>) {
+    let s = s.trim();
+    if !s.is_empty() {
+        result.push(s)
+    }
+}
+
+// whether `s` is exactly one control sequence followed by its arguments
+fn is_command(s: &str) -> bool {
+    if !s.starts_with('\\') {
+        return false;
+    }
+    let (name, after) = read_control(s, 0);
+    let (_, end) = read_args(s, after);
+    name.chars().all(|c| c.is_ascii_alphabetic() || c == '*') && end == s.len()
+}
+
+/// An argument of a command
+enum Arg<'a> {
+    Curly(&'a str),
+    Square(&'a str),
+}
+
+impl<'a> Arg<'a> {
+    fn inner(&self) -> &'a str {
+        match self {
+            Arg::Curly(s) | Arg::Square(s) => s,
+        }
+    }
+    fn to_option(&self) -> Options {
+        match self {
+            Arg::Curly(s) => Options::Curly(s.to_string()),
+            Arg::Square(s) => Options::Square(s.to_string()),
+        }
+    }
+}
+
+// reads the arguments directly following position `i`
+fn read_args(s: &str, mut i: usize) -> (Vec<Arg<'_>>, usize) {
+    let mut args = Vec::new();
+    loop {
+        match s.as_bytes().get(i) {
+            Some(b'{') => match read_group(s, i, b'{', b'}') {
+                Some((inner, end)) => {
+                    args.push(Arg::Curly(inner));
+                    i = end
+                }
+                None => break,
+            },
+            Some(b'[') => match read_group(s, i, b'[', b']') {
+                Some((inner, end)) => {
+                    args.push(Arg::Square(inner));
+                    i = end
+                }
+                None => break,
+            },
+            _ => break,
+        }
+    }
+    (args, i)
+}
+
+// reads the control sequence at `i` (which must be a backslash), returning its name and end
+pub(crate) fn read_control(s: &str, i: usize) -> (&str, usize) {
+    let bytes = s.as_bytes();
+    let mut end = i + 1;
+    while end < s.len() && bytes[end].is_ascii_alphabetic() {
+        end += 1
+    }
+    if end == i + 1 {
+        // a control symbol such as `\\` or `\%`
+        end = s[i + 1..]
+            .chars()
+            .next()
+            .map_or(s.len(), |c| i + 1 + c.len_utf8());
+    } else if bytes.get(end) == Some(&b'*') {
+        end += 1
+    }
+    (&s[i + 1..end], end)
+}
+
+// reads a balanced group opening at `i`, returning its content and the position after it
+pub(crate) fn read_group(s: &str, i: usize, open: u8, close: u8) -> Option<(&str, usize)> {
+    let bytes = s.as_bytes();
+    if bytes.get(i) != Some(&open) {
+        return None;
+    }
+    let mut depth = 0;
+    let mut j = i;
+    while j < s.len() {
+        match bytes[j] {
+            b'\\' => j += 1,
+            b'%' => j = line_end(s, j) - 1,
+            c if c == open => depth += 1,
+            c if c == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((&s[i + 1..j], j + 1));
+                }
+            }
+            _ => {}
+        }
+        j += 1
+    }
+    None
+}
+
+// finds the first `\begin{name}` and its matching `\end{name}`, returning the start of the
+// block, the start and end of its content and the end of the block
+fn find_env(s: &str, name: &str) -> Option<(usize, usize, usize, usize)> {
+    let mut depth = 0;
+    let mut start = None;
+    let mut inner_start = 0;
+    let mut i = 0;
+    while i < s.len() {
+        match s.as_bytes()[i] {
+            b'\\' => {
+                let (control, after) = read_control(s, i);
+                if control == "begin" || control == "end" {
+                    if let Some((env, end)) = read_group(s, after, b'{', b'}') {
+                        if env == name && control == "begin" {
+                            if depth == 0 {
+                                start = Some(i);
+                                inner_start = end;
+                            }
+                            depth += 1;
+                        } else if env == name && depth > 0 {
+                            depth -= 1;
+                            if depth == 0 {
+                                return Some((start?, inner_start, i, end));
+                            }
+                        }
+                        i = end;
+                        continue;
+                    }
+                }
+                i = after
+            }
+            b'%' if !VERBATIM.contains(&name) || depth == 0 => i = line_end(s, i),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+// Returns the name and the first curly argument of every use of the commands in `names`,
+// ignoring comments
+pub(crate) fn find_commands<'a>(s: &'a str, names: &[&str]) -> Vec<(&'a str, &'a str)> {
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < s.len() {
+        match s.as_bytes()[i] {
+            b'\\' => {
+                let (name, after) = read_control(s, i);
+                if names.contains(&name) {
+                    let (args, _) = read_args(s, after);
+                    if let Some(arg) = args.iter().find(|a| matches!(a, Arg::Curly(_))) {
+                        found.push((name, arg.inner()))
+                    }
+                }
+                i = after
+            }
+            b'%' => i = line_end(s, i),
+            _ => i += 1,
+        }
+    }
+    found
+}
+
+// whether only whitespace precedes position `i` on its line
+fn starts_line(s: &str, i: usize) -> bool {
+    let start = s[..i].rfind('\n').map_or(0, |n| n + 1);
+    s[start..i].trim().is_empty()
+}
+
+// returns the position after the newline ending the line containing `i`
+fn line_end(s: &str, i: usize) -> usize {
+    s[i..].find('\n').map_or(s.len(), |n| i + n + 1)
+}
+
+// Returns the first square and the first curly argument of every use of the commands in `names`,
+// ignoring comments
+pub(crate) fn find_commands_with_options<'a>(
+    s: &'a str,
+    names: &[&str],
+) -> Vec<(Option<&'a str>, &'a str)> {
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < s.len() {
+        match s.as_bytes()[i] {
+            b'\\' => {
+                let (name, after) = read_control(s, i);
+                if names.contains(&name) {
+                    let (args, _) = read_args(s, after);
+                    let square = args.iter().find(|a| matches!(a, Arg::Square(_)));
+                    if let Some(arg) = args.iter().find(|a| matches!(a, Arg::Curly(_))) {
+                        found.push((square.map(|a| a.inner()), arg.inner()))
+                    }
+                }
+                i = after
+            }
+            b'%' => i = line_end(s, i),
+            _ => i += 1,
+        }
+    }
+    found
+}
@@ -0,0 +1,215 @@
+use crate::Level::*;
+use crate::Type::*;
+use crate::*;
+use serde::{Deserialize, Serialize};
+
+/// The kind of element a label points to, used as the prefix of its key
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub enum LabelKind {
+    /// `part:`
+    Part,
+    /// `ch:`
+    Chapter,
+    /// `sec:`
+    Section,
+    /// `eq:`
+    Equation,
+    /// `tab:`
+    Table,
+    /// `fig:`
+    Figure,
+    /// `lst:`
+    Listing,
+    /// `thm:`
+    Theorem,
+    /// `item:`
+    Item,
+    /// No prefix
+    Other,
+}
+
+impl LabelKind {
+    /// Returns the prefix of the keys of this kind
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            LabelKind::Part => "part:",
+            LabelKind::Chapter => "ch:",
+            LabelKind::Section => "sec:",
+            LabelKind::Equation => "eq:",
+            LabelKind::Table => "tab:",
+            LabelKind::Figure => "fig:",
+            LabelKind::Listing => "lst:",
+            LabelKind::Theorem => "thm:",
+            LabelKind::Item => "item:",
+            LabelKind::Other => "",
+        }
+    }
+}
+
+/// Represents `\label{}` in latex, where the key is the kind's prefix followed by the name
+#[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub struct Label {
+    pub kind: LabelKind,
+    pub name: String,
+}
+
+impl Label {
+    pub fn new(kind: LabelKind, name: &str) -> Self {
+        Self {
+            kind,
+            name: name.to_string(),
+        }
+    }
+    /// Returns the key used by `\label{}` and `\ref{}`, like `sec:intro`
+    pub fn key(&self) -> String {
+        format!("{}{}", self.kind.prefix(), self.name)
+    }
+}
+
+impl Tex for Label {
+    fn to_latex_string(&self) -> String {
+        format!(r"\label{{{}}}", self.key())
+    }
+}
+
+/// The commands to refer to a label
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub enum RefType {
+    /// `\ref{}`
+    Ref,
+    /// `\eqref{}`, requires `amsmath`
+    EqRef,
+    /// `\pageref{}`
+    PageRef,
+    /// `\autoref{}`, requires `hyperref`
+    AutoRef,
+    /// `\cref{}`, requires `cleveref`
+    CRef,
+}
+
+impl RefType {
+    /// Returns the name of the command
+    pub fn command(&self) -> &'static str {
+        match self {
+            RefType::Ref => "ref",
+            RefType::EqRef => "eqref",
+            RefType::PageRef => "pageref",
+            RefType::AutoRef => "autoref",
+            RefType::CRef => "cref",
+        }
+    }
+}
+
+/// Represents a reference to a label like `\ref{sec:intro}`.
+///
+/// Use `to_latex_string()` to place it within a `Text::raw()`.
+#[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub struct Reference {
+    pub type_: RefType,
+    pub label: Label,
+}
+
+impl Reference {
+    pub fn new(type_: RefType, label: Label) -> Self {
+        Self { type_, label }
+    }
+}
+
+impl Tex for Reference {
+    fn to_latex_string(&self) -> String {
+        format!(r"\{}{{{}}}", self.type_.command(), self.label.key())
+    }
+}
+
+impl Requirements for Reference {
+    fn requirements(&self) -> Vec<Package> {
+        match self.type_ {
+            RefType::EqRef => vec![Package::new("amsmath")],
+            RefType::AutoRef => vec![Package::new("hyperref")],
+            RefType::CRef => vec![Package::new("cleveref")],
+            RefType::Ref | RefType::PageRef => Vec::new(),
+        }
+    }
+}
+
+impl From<Reference> for Element<Any> {
+    fn from(value: Reference) -> Self {
+        let requires = value.requirements();
+        let latex = value.to_latex_string();
+        let any = Any {
+            value: value.label.key(),
+            latex,
+            type_: T_Reference,
+            level: Document,
+            header_level: None,
+            text_type: None,
+            list_type: None,
+            items: None,
+            elements: None,
+            package_options: None,
+            modified: false,
+        };
+        Element::new_any(any).requiring(requires)
+    }
+}
+
+impl Element<Any> {
+    /// Attaches a label to the element, replacing any previous one.
+    ///
+    /// The label is placed before the `\end{}` of environments and after anything else.
+    pub fn set_label(&mut self, label: Label) {
+        let new = label.to_latex_string();
+        // the previous label is replaced where it was placed, never in a nested element
+        let previous = self
+            .label
+            .as_ref()
+            .map(|l| l.to_latex_string())
+            .filter(|old| {
+                let rest = self.latex.get(self.label_at..).unwrap_or_default();
+                rest.starts_with(old)
+            });
+        let (latex, at) = match previous {
+            Some(old) => {
+                let (start, end) = (self.label_at, self.label_at + old.len());
+                let latex = format!("{}{new}{}", &self.latex[..start], &self.latex[end..]);
+                (latex, start)
+            }
+            None => match (self.type_, self.latex.rfind(r"\end{")) {
+                (T_Environment, Some(i)) => {
+                    let latex = format!("{}{new}\n{}", &self.latex[..i], &self.latex[i..]);
+                    (latex, i)
+                }
+                _ => (format!("{}{new}", self.latex), self.latex.len()),
+            },
+        };
+        self.latex = latex.to_string();
+        self.value.latex = latex;
+        self.set_label_at(label, at)
+    }
+    // records a label already placed at `at` in the latex
+    pub(crate) fn set_label_at(&mut self, label: Label, at: usize) {
+        self.label = Some(label);
+        self.label_at = at;
+    }
+    // records a label already placed as the last one in the latex,
+    // like the line before the `\end{}` of a float
+    pub(crate) fn labelled_at_last(mut self, label: Option<Label>) -> Self {
+        if let Some(label) = label {
+            let at = self.latex.rfind(&label.to_latex_string());
+            debug_assert!(
+                at.is_some(),
+                "{} isn't in the latex",
+                label.to_latex_string()
+            );
+            match at {
+                Some(at) => self.set_label_at(label, at),
+                None => self.set_label(label),
+            }
+        }
+        self
+    }
+    /// Returns the label attached to the element
+    pub fn label(&self) -> Option<&Label> {
+        self.label.as_ref()
+    }
+}
@@ -1,11 +1,8 @@
-use crate::{Any, Element, ElementList, Input, Metadata, Tex};
+use crate::{Any, Element, ElementList, Error, Input, Metadata, Tex};
 use serde::{Deserialize, Serialize};
 use serde_json::{from_str, to_string_pretty};
 use std::fmt::{Display, Formatter};
 use std::fs::read_to_string;
-
-use std::io::Result;
-use std::num::ParseIntError;
 use std::path::PathBuf;
 use std::str::FromStr;
 
@@ -29,17 +26,17 @@ impl Template {
         }
     }
     /// Creates a new Template by deserializing a file using the path
-    pub fn from_file(path: PathBuf) -> Result<Self> {
+    pub fn from_file(path: PathBuf) -> Result<Self, Error> {
         let content = read_to_string(path)?;
-        Ok(from_str(&content).unwrap())
+        Self::from_string(&content)
     }
     /// Creates a new Template by deserializing a string
-    pub fn from_string(content: &str) -> Self {
-        from_str(content).unwrap()
+    pub fn from_string(content: &str) -> Result<Self, Error> {
+        Ok(from_str(content)?)
     }
     /// Serializes a Template into a JSON string
-    pub fn to_json_string(&self) -> String {
-        to_string_pretty(&self).unwrap()
+    pub fn to_json_string(&self) -> Result<String, Error> {
+        Ok(to_string_pretty(&self)?)
     }
     /// Returns a split string for a main file and input file
     pub async fn to_latex_split_string(&self, input: Input) -> (String, String) {
@@ -63,7 +60,7 @@ impl Template {
         main_path: PathBuf,
         str_path: PathBuf,
         input: Input,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         self.element_list
             .async_write_split(main_path, str_path, input)
             .await?;
@@ -112,29 +109,25 @@ impl Version {
         self.minor = minor;
         self.patch = patch;
     }
-    /// Returns the version of a `Cargo` project. 
+    /// Returns the version of a `Cargo` project.
     pub fn cargo_version() -> Self {
         let vers: &str = env!("CARGO_PKG_VERSION");
         Self::from_str(vers.trim()).unwrap()
     }
 }
 
-
 impl FromStr for Version {
-    type Err = ParseIntError;
+    type Err = Error;
 
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let split: Vec<&str> = s.split(".").collect();
-        let version = Version::new(
-            split[0].parse()?,
-            split[1].parse()?,
-            split[2].parse()?,
-        );
-        Ok(version)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |n: &str| n.parse().map_err(|_| Error::VersionParse(s.to_string()));
+        match s.split('.').collect::<Vec<&str>>().as_slice() {
+            [major, minor, patch] => Ok(Version::new(parse(major)?, parse(minor)?, parse(patch)?)),
+            _ => Err(Error::VersionParse(s.to_string())),
+        }
     }
 }
 
-
 /// Creates Template with default `v1.0.0`
 impl Default for Version {
     fn default() -> Self {
@@ -153,7 +146,6 @@ impl Display for Version {
     }
 }
 
-
 #[test]
 fn test_version() {
     let vers_str = "1.0.0";
@@ -163,8 +155,21 @@ fn test_version() {
 }
 
 #[test]
+fn test_invalid_version() {
+    assert!(Version::from_str("1.0").is_err());
+    assert!(Version::from_str("1.0.x").is_err());
+    assert!(Version::from_str("1.0.0.0").is_err());
+}
+
+#[test]
+fn test_invalid_template() {
+    assert!(Template::from_string("{ not json").is_err());
+}
+
+#[test]
+#[allow(clippy::bool_assert_comparison)]
 fn test_ge_le_version() {
     let ex = Version::new(3, 0, 0);
     let ex2 = Version::new(3, 1, 0);
     assert_eq!(ex2 > ex, true)
-}
\ No newline at end of file
+}
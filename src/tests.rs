@@ -49,3 +49,1040 @@ testing! {
         assert_eq!(&part.latex, expected_latex[6]);
     }
 }
+
+testing! {
+    fn test_header_levels(){
+        assert_eq!(Header::new("a", 1).to_latex_string(), r"\section{a}");
+        assert_eq!(Header::new("b", 2).to_latex_string(), r"\subsection{b}");
+        assert_eq!(Header::new("c", 3).to_latex_string(), r"\subsubsection{c}");
+    }
+    fn test_tree_sections(){
+        let mut tree = DocumentTree::new(&Metadata::default());
+        for name in ["One", "Two", "Three"] {
+            let mut section = Section::new(SectionLevel::Section, name);
+            section.push_section(Section::new(SectionLevel::Subsection, "First")).unwrap();
+            section.push_section(Section::new(SectionLevel::Subsection, "Second")).unwrap();
+            tree.push_section(section);
+        }
+        // append to section 3.2
+        let text = Text::new("appended", TextType::Normal);
+        tree.section_mut(&[2, 1]).unwrap().push(Element::from(text));
+        assert_eq!(tree.section(&[2, 1]).unwrap().elements().len(), 1);
+
+        // a section can't be nested inside a shallower one
+        let chapter = Section::new(SectionLevel::Chapter, "Chapter");
+        assert!(tree.insert_section(&[0, 0], chapter).is_err());
+
+        // move section 3 to the front
+        tree.move_section(&[2], &[0]).unwrap();
+        assert_eq!(tree.find("Three"), Some(vec![0]));
+        assert!(tree.move_section(&[5], &[0]).is_err());
+
+        let removed = tree.remove_section(&[1]).unwrap();
+        assert_eq!(removed.name(), "One");
+        assert_eq!(tree.sections().len(), 2);
+    }
+    fn test_tree_roundtrip(){
+        let mut list = ElementList::new(&Metadata::default());
+        list.push_array(Elements![
+            Package::new("amsmath"),
+            Text::new("intro", TextType::Normal),
+            Chapter::new("A chapter"),
+            Header::new("A section", 1),
+            Text::new("body", TextType::Normal),
+            Header::new("A subsection", 2),
+            Header::new("Another section", 1)
+        ]);
+        let tree = DocumentTree::from(list.clone());
+        assert_eq!(tree.preamble().len(), 1);
+        assert_eq!(tree.elements().len(), 1);
+        assert_eq!(tree.sections().len(), 1);
+        assert_eq!(tree.find("A subsection"), Some(vec![0, 0, 0]));
+        assert_eq!(tree.find("Another section"), Some(vec![0, 1]));
+        assert_eq!(tree.to_latex_string(), list.to_latex_string());
+    }
+}
+
+testing! {
+    fn test_escape(){
+        let text = Text::new(r"R&D 100% {beta}_v2 ~^\ #1 $5", TextType::Normal);
+        let expected = r"R\&D 100\% \{beta\}\_v2 \textasciitilde{}\textasciicircum{}\textbackslash{} \#1 \$5";
+        assert_eq!(text.to_latex_string(), expected);
+        assert_eq!(Chapter::new("Q&A").to_latex_string(), r"\chapter{Q\&A}");
+        assert_eq!(Item::new("50%").to_latex_string(), r"\item {50\%}");
+    }
+    fn test_raw_text(){
+        assert_eq!(Text::raw(r"\LaTeX{}", TextType::Bold).to_latex_string(), r"\textbf{\LaTeX{}}");
+        assert_eq!(Text::new(r"x_1^2", TextType::Math).to_latex_string(), r"$x_1^2$");
+        assert_eq!(Header::raw(r"\emph{a}", 2).to_latex_string(), r"\subsection{\emph{a}}");
+
+        let metadata = Metadata::new("A & B", r"\today", "50%", 11, "letterpaper", "article", true);
+        assert!(metadata.to_latex_string().contains(r"\author{A \& B}"));
+        assert!(metadata.to_latex_string().contains(r"\title{50\%}"));
+        assert!(metadata.to_latex_string().contains(r"\date{\today}"));
+        let metadata = metadata.raw();
+        assert!(metadata.to_latex_string().contains(r"\author{A & B}"));
+    }
+}
+
+testing! {
+    fn test_parse_latex(){
+        let source = r"\documentclass[12pt, a4paper]{report}
+\usepackage{amsmath, graphicx}
+\newcommand{\R}{\mathbb{R}}
+\title{A \emph{title}}
+\author{Me}
+\date{\today}
+\begin{document}
+\maketitle
+% a comment
+\chapter{Intro}
+Some text with 100\% and $x^2$.
+
+Another paragraph.
+
+\newpage
+\section{Lists}
+\begin{itemize}
+    \item first
+    \item {second}
+\end{itemize}
+\begin{tabular}{ll}
+a & b \\
+\end{tabular}
+\section*{Unnumbered}
+\end{document}
+";
+        let list = ElementList::from_latex(source);
+        let metadata = list.metadata();
+        assert_eq!(metadata.doc_class, "report");
+        assert_eq!(metadata.fontsize, Some(12));
+        assert_eq!(metadata.papersize.as_deref(), Some("a4paper"));
+        assert_eq!(metadata.title.as_deref(), Some(r"A \emph{title}"));
+        assert!(metadata.maketitle);
+
+        let types: Vec<Type> = list.iter().map(|e| e.type_).collect();
+        assert_eq!(types, vec![
+            Type::T_Package,
+            Type::T_Package,
+            Type::T_Custom,
+            Type::T_Custom,
+            Type::T_Chapter,
+            Type::T_Text,
+            Type::T_Custom,
+            Type::T_Header,
+            Type::T_List,
+            Type::T_Environment,
+            Type::T_Custom,
+        ]);
+        let latex = list.to_latex_string();
+        assert!(latex.contains(r"\title{A \emph{title}}"));
+        assert!(latex.contains("Some text with 100\\% and $x^2$.\n\nAnother paragraph."));
+        assert!(latex.contains("\\begin{itemize}\n\\item {first}\n\\item {second}\n\\end{itemize}"));
+        assert!(latex.contains("\\begin{tabular}{ll}\na & b \\\\\n\\end{tabular}"));
+
+        // parsing the output again gives back the same list
+        assert_eq!(ElementList::from_latex(&latex).to_latex_string(), latex);
+    }
+}
+
+testing! {
+    fn test_metadata_builder(){
+        let beamer = Metadata::builder()
+            .doc_class("beamer")
+            .title("Slides")
+            .option(ClassOption::Custom("aspectratio=169".to_string()))
+            .build();
+        assert_eq!(beamer.to_latex_string(), "\\documentclass[aspectratio=169]{beamer}\n\\title{Slides}");
+
+        let standalone = Metadata::builder().doc_class("standalone").build();
+        assert_eq!(standalone.to_latex_string(), r"\documentclass{standalone}");
+
+        let book = Metadata::builder()
+            .doc_class("book")
+            .fontsize(12)
+            .papersize("a4paper")
+            .options(vec![ClassOption::TwoColumn, ClassOption::Draft, ClassOption::OpenAny])
+            .build();
+        assert_eq!(book.to_latex_string(), r"\documentclass[12pt, a4paper, twocolumn, draft, openany]{book}");
+
+        let list = ElementList::from_latex(&ElementList::new(&book).to_latex_string());
+        assert_eq!(list.metadata().options, book.options);
+        assert_eq!(list.metadata().to_latex_string(), book.to_latex_string());
+    }
+}
+
+testing! {
+    fn test_package_options(){
+        let pkg = Package::with_options("geometry", vec![
+            PackageOption::from("margin=1in"),
+            PackageOption::Flag("landscape".to_string()),
+        ]);
+        assert_eq!(pkg.to_latex_string(), r"\usepackage[margin=1in, landscape]{geometry}");
+
+        let mut pkg = Package::new("inputenc");
+        pkg.modify_element(vec![Options::Square("utf8".to_string())]);
+        assert_eq!(Element::from(pkg).latex, r"\usepackage[utf8]{inputenc}");
+    }
+    fn test_package_merging(){
+        let mut list = ElementList::new(&Metadata::default());
+        list.push_array(Elements![
+            Package::new("cleveref"),
+            Package::new("hyperref"),
+            Custom::new(r"\hypersetup{colorlinks}", Level::Packages),
+            Package::with_options("geometry", vec![PackageOption::from("margin=1in")]),
+            Package::new("amsmath"),
+            Package::with_options("geometry", vec![
+                PackageOption::from("margin=2in"),
+                PackageOption::from("a4paper"),
+            ]),
+            Package::new("amsmath")
+        ]);
+        let packages: Vec<String> = list.packages().into_iter().map(|e| e.latex).collect();
+        assert_eq!(packages, vec![
+            r"\usepackage[margin=1in, a4paper]{geometry}",
+            r"\usepackage{amsmath}",
+            r"\usepackage{hyperref}",
+            r"\hypersetup{colorlinks}",
+            r"\usepackage{cleveref}",
+        ]);
+        assert_eq!(list.package_conflicts().len(), 1);
+        assert_eq!(list.to_latex_string().matches(r"\usepackage{amsmath}").count(), 1);
+
+        let mut hyperref = Package::new("hyperref");
+        hyperref.modify_element(vec![Options::Square("colorlinks".to_string())]);
+        let mut list = ElementList::new(&Metadata::default());
+        list.push_array(Elements![hyperref, Package::new("amsmath")]);
+        let packages: Vec<String> = list.packages().into_iter().map(|e| e.latex).collect();
+        assert_eq!(packages, vec![r"\usepackage{amsmath}", r"\usepackage[colorlinks]{hyperref}"]);
+    }
+}
+
+testing! {
+    fn test_required_packages(){
+        use crate::bundle::graphicx::GraphicInclude;
+        use crate::bundle::tables::*;
+        let mut env = Environment::new("center");
+        env.push(Element::from(GraphicInclude::new(PathBuf::from("a.png"))));
+        let mut align = Environment::new("align*");
+        align.push(Element::from(Text::raw(r"x &= 1", TextType::Normal)));
+        let table = Table::new(
+            None,
+            vec![
+                Column::new(Position::Middle(2.0), Separator::Single),
+                Column::new(Position::Centered, Separator::Single),
+            ],
+            vec![Row::new(Elements![
+                Text::new("a", TextType::Normal),
+                crate::bundle::math::Misc::Square
+            ])],
+            false,
+            false,
+        );
+        let mut list = ElementList::new(&Metadata::default());
+        list.push_array(Elements![Package::new("graphicx"), env, align, table]);
+
+        let packages: Vec<String> = list.packages().into_iter().map(|e| e.latex).collect();
+        assert_eq!(packages, vec![
+            r"\usepackage{graphicx}",
+            r"\usepackage{amsmath}",
+            r"\usepackage{array}",
+            r"\usepackage{amssymb}",
+        ]);
+        assert!(list.to_latex_string().contains(r"\usepackage{amsmath}"));
+    }
+}
+
+testing! {
+    fn test_labels(){
+        let mut header = Element::from(Header::new("Intro", 1));
+        header.set_label(Label::new(LabelKind::Section, "intro"));
+        assert_eq!(header.latex, r"\section{Intro}\label{sec:intro}");
+        header.set_label(Label::new(LabelKind::Section, "start"));
+        assert_eq!(header.latex, r"\section{Intro}\label{sec:start}");
+
+        let mut eq = Environment::new("equation");
+        eq.push(Element::from(Text::raw("E = mc^2", TextType::Normal)));
+        let mut eq = Element::from(eq);
+        eq.set_label(Label::new(LabelKind::Equation, "energy"));
+        assert_eq!(eq.latex, "\\begin{equation}\nE = mc^2\n\\label{eq:energy}\n\\end{equation}");
+
+        // a nested element's identical label is left alone
+        let mut figure = Environment::new("figure");
+        figure.push(Element::from(Text::raw(r"\label{fig:a}", TextType::Normal)));
+        let mut figure = Element::from(figure);
+        figure.set_label(Label::new(LabelKind::Figure, "a"));
+        figure.set_label(Label::new(LabelKind::Figure, "b"));
+        assert_eq!(figure.latex, "\\begin{figure}\n\\label{fig:a}\n\\label{fig:b}\n\\end{figure}");
+
+        let reference = Reference::new(RefType::EqRef, Label::new(LabelKind::Equation, "energy"));
+        assert_eq!(reference.to_latex_string(), r"\eqref{eq:energy}");
+    }
+    fn test_check_references(){
+        let mut header = Element::from(Header::new("Intro", 1));
+        header.set_label(Label::new(LabelKind::Section, "intro"));
+        let mut list = ElementList::new(&Metadata::default());
+        list.push(header.clone());
+        list.push(Element::from(Reference::new(RefType::CRef, Label::new(LabelKind::Section, "intro"))));
+        let see = Reference::new(RefType::Ref, Label::new(LabelKind::Section, "intro")).to_latex_string();
+        list.push(Element::from(Text::raw(&format!("See {see}."), TextType::Normal)));
+        assert!(list.validate().is_empty());
+        assert!(list.check().is_ok());
+        assert!(list.to_latex_string().contains(r"\usepackage{cleveref}"));
+
+        list.push(header);
+        list.push(Element::from(Text::raw(r"\cref{sec:intro,sec:missing} % \ref{sec:ignored}", TextType::Normal)));
+        assert_eq!(list.validate(), vec![
+            Diagnostic::DuplicateLabel("sec:intro".to_string()),
+            Diagnostic::DanglingReference("sec:missing".to_string()),
+        ]);
+        assert!(list.check().is_err());
+    }
+}
+
+testing! {
+    fn test_bib_entries(){
+        let entry = Entry::new(EntryType::Article, "knuth84")
+            .field(Field::Author, "Donald E. Knuth")
+            .field(Field::Title, "Literate Programming")
+            .field(Field::Year, "1983");
+        assert_eq!(entry.missing_fields(), vec![Field::Journal]);
+        let entry = entry.field(Field::Journal, "The Computer Journal").field(Field::Year, "1984");
+        assert!(entry.missing_fields().is_empty());
+        assert_eq!(entry.to_latex_string(), "@article{knuth84,\n  author = {Donald E. Knuth},\n  title = {Literate Programming},\n  year = {1984},\n  journal = {The Computer Journal},\n}");
+
+        let mut bib = Bibliography::new();
+        bib.push(entry);
+        bib.push(Entry::new(EntryType::Misc, "web"));
+        bib.push(Entry::new(EntryType::Misc, "web").field(Field::Note, "replaced"));
+        assert_eq!(bib.entries().len(), 2);
+        assert_eq!(bib.get("web").unwrap().get(&Field::Note), Some("replaced"));
+    }
+    fn test_citations(){
+        let mut cite = Citation::new(CiteType::ParenCite, vec!["knuth84", "web"]);
+        cite.postnote = Some("p.~5".to_string());
+        assert_eq!(cite.to_latex_string(), r"\parencite[p.~5]{knuth84,web}");
+
+        let print = PrintBibliography::new(Backend::BibTex, "plain", PathBuf::from("refs.bib"));
+        assert_eq!(print.to_latex_string(), "\\bibliographystyle{plain}\n\\bibliography{refs}");
+        let print = PrintBibliography::new(Backend::BibLatex, "authoryear", PathBuf::from("refs.bib"));
+
+        let mut list = ElementList::new(&Metadata::default());
+        list.push(Element::from(cite));
+        list.push_array(print.build());
+        let latex = list.to_latex_string();
+        assert!(latex.contains("\\usepackage[style=authoryear]{biblatex}\n\\addbibresource{refs.bib}"));
+        assert!(latex.contains("\\printbibliography\n\\end{document}"));
+        assert!(list.validate().is_empty());
+
+        let mut bib = Bibliography::new();
+        bib.push(Entry::new(EntryType::Misc, "web"));
+        list.set_bibliography(bib);
+        list.push(Element::from(Text::raw(r"\nocite{*}", TextType::Normal)));
+        assert_eq!(list.validate(), vec![Diagnostic::MissingCitation("knuth84".to_string())]);
+    }
+}
+
+testing! {
+    fn test_math_expr(){
+        use crate::bundle::math::*;
+        let x = Expr::var("x");
+        let alpha = Expr::from(Greek::Alpha(Case::Lower));
+        let poly = 3 * x.clone().pow(2) - (x.clone() + 1) * alpha.clone();
+        assert_eq!(poly.to_latex_string(), r"3 \cdot x^{2} - (x + 1) \cdot \alpha");
+        assert_eq!((x.clone() - (x.clone() - 1)).to_latex_string(), "x - (x - 1)");
+        assert_eq!((-x.clone()).pow(2).to_latex_string(), "(-x)^{2}");
+        assert_eq!(((x.clone() + 1) / 2).to_latex_string(), r"\frac{x + 1}{2}");
+        let half = Expr::frac(x.clone(), 2);
+        assert_eq!(half.clone().pow(2).to_latex_string(), r"(\frac{x}{2})^{2}");
+        assert_eq!((3 * half.clone()).to_latex_string(), r"3 \cdot \frac{x}{2}");
+        assert_eq!(Expr::root(3, x.clone()).subscript(1).to_latex_string(), r"(\sqrt[3]{x})_{1}");
+
+        let i = Expr::var("i");
+        let sum = Expr::sum(Some(i.clone().equals(1)), Some(Expr::var("n")), x.clone().subscript(i));
+        // a big operator followed by a factor would take it into its body
+        let y = Expr::var("y");
+        assert_eq!((sum.clone() * y.clone()).to_latex_string(), r"(\sum_{i = 1}^{n} x_{i}) \cdot y");
+        assert_eq!((y.clone() * sum.clone()).to_latex_string(), r"y \cdot \sum_{i = 1}^{n} x_{i}");
+        assert_eq!(sum.to_latex_string(), r"\sum_{i = 1}^{n} x_{i}");
+        let integral = Expr::integral(Some(0.into()), Some(Misc::Infty.into()), Expr::func(Func::Exp, -x.clone()), x.clone());
+        assert_eq!(integral.to_latex_string(), r"\int_{0}^{\infty} \exp\left(-x\right) \, dx");
+        let limit = Expr::limit(x.clone(), 0, Expr::frac(Expr::func(Func::Sin, x.clone()), x.clone()));
+        assert_eq!(limit.to_latex_string(), r"\lim_{x \to 0} \frac{\sin\left(x\right)}{x}");
+        let norm = Expr::root(3, x.clone()).delim(Delimiter::Norm).rel(Binary::Leq, 1.5);
+        assert_eq!(norm.to_latex_string(), r"\left\| \sqrt[3]{x} \right\| \leq 1.5");
+
+        let element = Element::from(x.rel(Binary::In, Misc::VarNothing));
+        assert_eq!(element.requirements(), vec![Package::new("amssymb")]);
+    }
+}
+
+testing! {
+    fn test_multiline_math(){
+        use crate::bundle::math::*;
+        let x = Expr::var("x");
+        let mut align = MultiLine::new(MultiLineKind::Align, false);
+        align.push(MathLine::relation(Some(x.clone().pow(2) - 1), "=", (x.clone() - 1) * (x.clone() + 1)).label(Label::new(LabelKind::Equation, "square")));
+        align.push(MathLine::relation(None, "=", Expr::var("y")).nonumber());
+        assert_eq!(align.to_latex_string(), "\\begin{align}\nx^{2} - 1 &= (x - 1) \\cdot (x + 1) \\label{eq:square} \\\\\n &= y \\nonumber\n\\end{align}");
+
+        let mut alignat = MultiLine::new(MultiLineKind::AlignAt(2), true);
+        alignat.push(MathLine::new(vec!["a", "= b", "c", "= d"]));
+        assert_eq!(alignat.to_latex_string(), "\\begin{alignat*}{2}\na &= b &c &= d\n\\end{alignat*}");
+
+        let cases = Cases::new().row(1, x.clone().rel(Binary::Geq, 0)).row(0, Expr::text("otherwise"));
+        let abs = Expr::func(Func::Custom("sgn".to_string()), x).equals(cases);
+        assert_eq!(abs.to_latex_string(), "\\operatorname{sgn}\\left(x\\right) = \\begin{cases}\n1 & x \\geq 0 \\\\\n0 & \\text{otherwise}\n\\end{cases}");
+
+        let mut list = ElementList::new(&Metadata::default());
+        list.push(Element::from(align));
+        list.push(Element::from(Reference::new(RefType::EqRef, Label::new(LabelKind::Equation, "square"))));
+        assert!(list.validate().is_empty());
+        assert!(list.to_latex_string().contains(r"\usepackage{amsmath}"));
+
+        // gather has no alignment points, except within nested environments
+        let mut gather = MultiLine::new(MultiLineKind::Gather, true);
+        gather.push(MathLine::new(vec![r"\begin{matrix} a & b \end{matrix}"]));
+        gather.push(MathLine::new(vec![r"a \& b"]));
+        assert!(gather.check().is_ok());
+        gather.push(MathLine::new(vec!["a", "= b"]));
+        gather.push(MathLine::new(vec!["c = d"]).label(Label::new(LabelKind::Equation, "unnumbered")));
+        assert!(gather.check().is_err());
+        list.push(Element::from(gather));
+        assert_eq!(list.validate(), vec![
+            Diagnostic::Invalid(2, "line 2 of `gather*` has an alignment point".to_string()),
+            Diagnostic::Invalid(2, "line 3 of `gather*` has a label but no number".to_string()),
+        ]);
+
+        // split only works in display math, so it is placed within equation
+        let mut split = MultiLine::new(MultiLineKind::Split, false);
+        split.push(MathLine::new(vec!["a", "= b"]));
+        assert_eq!(split.to_latex_string(), "\\begin{split}\na &= b\n\\end{split}");
+        let mut element = Element::from(split);
+        element.set_label(Label::new(LabelKind::Equation, "split"));
+        assert_eq!(element.latex, "\\begin{equation}\n\\begin{split}\na &= b\n\\end{split}\n\\label{eq:split}\n\\end{equation}");
+    }
+}
+
+testing! {
+    fn test_matrix(){
+        use crate::bundle::math::*;
+        let m = Matrix::new(MatrixKind::Bracket, vec![vec![1, 2], vec![3, 4]]);
+        assert_eq!(m.to_latex_string(), "\\begin{bmatrix}\n1 & 2 \\\\\n3 & 4\n\\end{bmatrix}");
+        assert_eq!(m.requirements(), vec![Package::new("amsmath")]);
+
+        let m = Matrix::with_precision(MatrixKind::Paren, [[1.0, 0.5, 2.0], [0.0, 1.25, -1.0]], 2).augment(2);
+        assert_eq!(m.to_latex_string(), "\\left(\\begin{array}{cc|c}\n1.00 & 0.50 & 2.00 \\\\\n0.00 & 1.25 & -1.00\n\\end{array}\\right)");
+        assert!(m.requirements().is_empty());
+
+        let data: Vec<Vec<usize>> = (0..6).map(|r| (0..6).map(|c| r * 6 + c).collect()).collect();
+        let m = Matrix::new(MatrixKind::Array(vec![ColumnAlign::Right]), data).truncate(4, 3).augment(4);
+        assert_eq!(m.to_latex_string(), "\\begin{array}{rc|c}\n0 & \\cdots & 5 \\\\\n6 & \\cdots & 11 \\\\\n\\vdots & \\ddots & \\vdots \\\\\n30 & \\cdots & 35\n\\end{array}");
+
+        let x = Expr::var("x");
+        let m = Matrix::from_tex(MatrixKind::Vert, vec![vec![x.clone(), Expr::Int(1)], vec![Expr::Int(0), x.pow(2)]]);
+        let det = Expr::var("D").equals(m);
+        assert_eq!(det.to_latex_string(), "D = \\begin{vmatrix}\nx & 1 \\\\\n0 & x^{2}\n\\end{vmatrix}");
+    }
+}
+
+testing! {
+    fn test_math_symbols(){
+        use crate::bundle::math::*;
+        assert_eq!(lookup(r"\prec").unwrap().category, Category::Relation);
+        assert_eq!(lookup("mathbb").unwrap().apply("R").to_latex_string(), r"\mathbb{R}");
+        assert!(lookup("mathbb").unwrap().takes_argument());
+        assert!(lookup("nosuchsymbol").is_none());
+        assert_eq!(symbols(Category::BigOperator).find(|s| s.unicode == Some('⋃')).unwrap().name, "bigcup");
+
+        let element = Element::from(lookup("iint").unwrap());
+        assert_eq!(element.latex, r"\iint");
+        assert_eq!(element.requirements(), vec![Package::new("amsmath")]);
+        let element = Element::from(Misc::Complement);
+        assert_eq!(element.requirements(), vec![Package::new("amssymb")]);
+        // the requirements of the symbol enums come from the catalogue
+        for symbol in [Misc::Square.requirements(), Misc::VarNothing.requirements(), Binary::Box.requirements(), Binary::Nothing.requirements()] {
+            assert_eq!(symbol, vec![Package::new("amssymb")]);
+        }
+        assert!(Misc::Infty.requirements().is_empty() && Binary::Leq.requirements().is_empty());
+
+        // every command of the symbol enums is in the catalogue
+        let mut latex: Vec<String> = vec![
+            Misc::Infty, Misc::ForAll, Misc::Re, Misc::Im, Misc::Nabla, Misc::Exists,
+            Misc::NExists, Misc::Partial, Misc::EmptySet, Misc::VarNothing, Misc::Wp,
+            Misc::Complement, Misc::Neg, Misc::CDots, Misc::Square, Misc::Surd,
+            Misc::BlackSquare, Misc::Triangle,
+        ].iter().map(|s| s.to_latex_string()).collect();
+        latex.extend([
+            Binary::Times, Binary::Div, Binary::CDot, Binary::Cap, Binary::Cup, Binary::Neq,
+            Binary::Leq, Binary::Geq, Binary::In, Binary::Perp, Binary::Nothing, Binary::Subset,
+            Binary::Simeq, Binary::Approx, Binary::Wegde, Binary::Vee, Binary::OPlus,
+            Binary::OTimes, Binary::Box, Binary::BoxTimes, Binary::Equiv, Binary::Cong,
+        ].iter().map(|s| s.to_latex_string()));
+        latex.extend([
+            Arrows::LeftArrow(Case::Upper), Arrows::RightArrow(Case::Lower),
+            Arrows::LeftRightArrow(Case::Upper), Arrows::RightLeftHarpoons, Arrows::UpArrow(Case::Lower),
+            Arrows::DownArrow(Case::Upper), Arrows::UpdownArrow, Arrows::Mapsto, Arrows::LongMapsto,
+            Arrows::Nearrow, Arrows::Searrow, Arrows::Swarrow, Arrows::Nwarrow, Arrows::LeftHarpoonUp,
+            Arrows::LeftHarpoonDown, Arrows::RightHarpoonUp, Arrows::RightHarpoonDown,
+        ].iter().map(|s| s.to_latex_string()));
+        latex.extend([
+            Greek::Alpha(Case::Lower), Greek::VarEpsilon, Greek::Theta(Case::Upper), Greek::VarRho,
+            Greek::VarPhi, Greek::Omega(Case::Upper), Greek::Upsilon(Case::Lower),
+        ].iter().map(|s| s.to_latex_string()));
+        for latex in latex {
+            assert!(lookup(&latex).is_some(), "{latex} is missing");
+        }
+    }
+}
+
+testing! {
+    fn test_unicode_math(){
+        use crate::bundle::math::*;
+        assert_eq!(unicode_to_latex("α² + β₁"), r"\alpha^{2} + \beta_{1}");
+        assert_eq!(
+            unicode_to_latex("∀ε>0 ∃δ: |x−a|<δ ⇒ |f(x)−L|<ε"),
+            r"\forall\varepsilon>0 \exists\delta: |x-a|<\delta \Rightarrow |f(x)-L|<\varepsilon"
+        );
+        assert_eq!(unicode_to_latex("∀x∈ℝ, x̂ ≤ α̂ⁿ⁺¹"), r"\forall x\in\mathbb{R}, \hat{x} \leq \hat{\alpha}^{n+1}");
+        assert_eq!(unicode_to_latex("50% & #1 ∈ {a}"), r"50\% \& \#1 \in \{a\}");
+        assert_eq!(unicode_to_latex("ΑΒΓ"), r"AB\Gamma");
+        // ascii scripts as written in markdown, where a lone `^` or `_` is escaped
+        assert_eq!(unicode_to_latex("x^2 + a_ij ≤ e^x"), r"x^{2} + a_{ij} \leq e^{x}");
+        assert_eq!(unicode_to_latex("a _ ^"), r"a \_ \hat{}");
+        assert_eq!(UnicodeMath::new("x^2").to_mathml(), "<mrow><msup><mi>x</mi><mrow><mn>2</mn></mrow></msup></mrow>");
+        let math = UnicodeMath::new("∄x ∈ ∅");
+        assert_eq!(math.requirements(), vec![Package::new("amssymb")]);
+        let element = Element::from(math);
+        assert_eq!(element.latex, r"$\nexists x \in \emptyset$");
+        assert_eq!(element.requirements(), vec![Package::new("amssymb")]);
+    }
+}
+
+testing! {
+    fn test_mathml(){
+        use crate::bundle::math::*;
+        let x = Expr::var("x");
+        let expr = Expr::frac(x.clone().pow(2) + 1, Greek::Pi(Case::Lower));
+        assert_eq!(expr.to_mathml(), "<mfrac><mrow><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo><mn>1</mn></mrow><mi>π</mi></mfrac>");
+        let expr = (x.clone() - 1) * lookup("hat").unwrap().apply("y");
+        assert_eq!(expr.to_mathml(), r#"<mrow><mrow><mo>(</mo><mrow><mi>x</mi><mo>−</mo><mn>1</mn></mrow><mo>)</mo></mrow><mo>⋅</mo><mover accent="true"><mi>y</mi><mo>^</mo></mover></mrow>"#);
+        let sum = Expr::sum(Some(Expr::var("i").equals(1)), Some(Expr::var("n")), x.clone().subscript("i"));
+        assert_eq!(sum.to_mathml_inline(), r#"<math xmlns="http://www.w3.org/1998/Math/MathML"><mrow><munderover><mo largeop="true">∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover><msub><mi>x</mi><mi>i</mi></msub></mrow></math>"#);
+        assert_eq!(Binary::Leq.to_mathml(), "<mo>≤</mo>");
+        assert_eq!(Misc::Infty.to_mathml(), "<mi>∞</mi>");
+
+        let m = Matrix::new(MatrixKind::Bracket, vec![vec![1, -2]]);
+        assert_eq!(m.to_mathml(), "<mrow><mo>[</mo><mtable><mtr><mtd><mn>1</mn></mtd><mtd><mrow><mo>−</mo><mn>2</mn></mrow></mtd></mtr></mtable><mo>]</mo></mrow>");
+
+        let mut align = MultiLine::new(MultiLineKind::Align, false);
+        align.push(MathLine::relation(Some(x.clone()), "<", 2).label(Label::new(LabelKind::Equation, "bound")));
+        assert_eq!(align.to_mathml_block(), r#"<math xmlns="http://www.w3.org/1998/Math/MathML" display="block"><mtable columnalign="right left"><mtr id="eq:bound"><mtd><mi>x</mi></mtd><mtd><mrow><mrow></mrow><mo>&lt;</mo><mn>2</mn></mrow></mtd></mtr></mtable></math>"#);
+
+        let unicode = UnicodeMath::new("α² ≤ x̂");
+        assert_eq!(unicode.to_mathml(), r#"<mrow><msup><mi>α</mi><mrow><mn>2</mn></mrow></msup><mo>≤</mo><mover accent="true"><mi>x</mi><mo>^</mo></mover></mrow>"#);
+
+        let m = Matrix::from_exprs(MatrixKind::Paren, vec![vec![x.clone().pow(2), Expr::from(1)]]);
+        assert_eq!(m.to_latex_string(), "\\begin{pmatrix}\nx^{2} & 1\n\\end{pmatrix}");
+        assert_eq!(m.to_mathml(), "<mrow><mo>(</mo><mtable><mtr><mtd><msup><mi>x</mi><mn>2</mn></msup></mtd><mtd><mn>1</mn></mtd></mtr></mtable><mo>)</mo></mrow>");
+        let m = Matrix::from_tex(MatrixKind::Plain, vec![vec![Text::raw("x^{2}", TextType::Normal)]]);
+        assert_eq!(m.to_mathml(), "<mtable><mtr><mtd><msup><mi>x</mi><mn>2</mn></msup></mtd></mtr></mtable>");
+
+        let lines = vec![Element::from(Text::raw("x &= \\frac{1}{2} \\\\", TextType::Normal)), Element::from(Text::raw("&= y", TextType::Normal))];
+        let env = equation_split_env(true, lines);
+        assert_eq!(env.to_mathml(), r#"<mtable columnalign="right left"><mtr><mtd><mi>x</mi></mtd><mtd><mrow><mo>=</mo><mfrac><mn>1</mn><mn>2</mn></mfrac></mrow></mtd></mtr><mtr><mtd><mrow></mrow></mtd><mtd><mrow><mo>=</mo><mi>y</mi></mrow></mtd></mtr></mtable>"#);
+        let text = Text::new("\\sqrt[3]{x_{1}}", TextType::Math);
+        assert_eq!(text.to_mathml(), "<mroot><msub><mi>x</mi><mn>1</mn></msub><mn>3</mn></mroot>");
+        assert_eq!(Text::new("a < b", TextType::Normal).to_mathml(), "<mtext>a &lt; b</mtext>");
+        // unclosed braces are left out instead of parsed again
+        assert_eq!(Text::new("{x", TextType::Math).to_mathml(), "<mi>x</mi>");
+        assert_eq!(Text::new("\\frac{x", TextType::Math).to_mathml(), "<mfrac><mrow></mrow><mi>x</mi></mfrac>");
+    }
+}
+
+testing! {
+    fn test_check_math(){
+        use crate::bundle::math::*;
+        use crate::bundle::tables::{Column, Position, Row, Separator, Table};
+        use crate::bundle::theorem::{Theorem, TheoremKind};
+        assert!(check_math(r"\left( \frac{\alpha}{\sin x} \right) \leq \mathbb{R}_{n}, \{ 1 \}").is_empty());
+        assert_eq!(check_math(r"\frac{a}{b"), vec![MathIssue::UnclosedBrace(8)]);
+        assert_eq!(check_math(r"a} + \right)"), vec![
+            MathIssue::UnexpectedBrace(1),
+            MathIssue::UnmatchedRight(5),
+        ]);
+        assert_eq!(check_math(r"{\left( x} \alpah \textbackslash"), vec![
+            MathIssue::UnmatchedLeft(1),
+            MathIssue::UnknownCommand(11, "alpah".to_string()),
+            MathIssue::TextModeCommand(18, "textbackslash".to_string()),
+        ]);
+
+        let mut list = ElementList::new(&Metadata::default());
+        list.push(Element::from(Custom::new(r"\newcommand{\R}{\mathbb{R}}", Level::Packages)));
+        list.push(Element::from(Text::new(r"x \in \R", TextType::Math)));
+        list.push(Element::from(Text::new(r"\section{x}", TextType::Math)));
+        let mut align = MultiLine::new(MultiLineKind::Align, true);
+        align.push(MathLine::new(vec![r"\frac{1}{2", "= y"]));
+        list.push(Element::from(align));
+        assert_eq!(list.check_math(), vec![
+            Diagnostic::Math(2, MathIssue::TextModeCommand(0, "section".to_string())),
+            Diagnostic::Math(3, MathIssue::UnclosedBrace(23)),
+        ]);
+        assert!(list.check().is_err());
+
+        let mut list = ElementList::new(&Metadata::default());
+        list.push(Element::from(Custom::new(r"\newcommand*{\N}{\mathbb{N}} \newcommand\Z{\mathbb{Z}}", Level::Packages)));
+        list.push(Element::from(Text::new(r"\N \subset \Z \implies \sum_{\substack{i \in \N}} x \dotsb \hspace{1em} \coth x", TextType::Math)));
+        let mut pmatrix = Environment::new("pmatrix");
+        pmatrix.push(Element::from(Text::raw(r"\alpah & 1", TextType::Normal)));
+        list.push(Element::from(pmatrix));
+        assert_eq!(list.check_math(), vec![
+            Diagnostic::Math(2, MathIssue::UnknownCommand(16, "alpah".to_string())),
+        ]);
+        // unknown commands are warnings that don't fail the check
+        assert!(list.validate()[0].is_warning());
+        assert!(list.check().is_ok());
+
+        // math nested in other elements is checked too
+        let mut theorem = Theorem::new(&TheoremKind::lemma());
+        theorem.push(Element::from(Text::new(r"\section{x}", TextType::Math)));
+        list.push(Element::from(theorem));
+        let columns = vec![Column::new(Position::Left, Separator::None)];
+        let rows = vec![Row::new(Elements![Text::new(r"\frac{a", TextType::Math)])];
+        list.push(Element::from(Table::new(None, columns, rows, false, false)));
+        assert_eq!(list.check_math()[1..], [
+            Diagnostic::Math(3, MathIssue::TextModeCommand(0, "section".to_string())),
+            Diagnostic::Math(4, MathIssue::UnclosedBrace(5)),
+        ]);
+    }
+}
+
+testing! {
+    fn test_theorems(){
+        use crate::bundle::theorem::*;
+        let theorem = TheoremKind::theorem().within("section");
+        let lemma = TheoremKind::lemma().shared(&theorem);
+        let remark = TheoremKind::remark().unnumbered();
+        assert_eq!(lemma.declarations(), vec![
+            "\\theoremstyle{plain}\n\\newtheorem{theorem}{Theorem}[section]".to_string(),
+            "\\theoremstyle{plain}\n\\newtheorem{lemma}[theorem]{Lemma}".to_string(),
+        ]);
+        assert_eq!(remark.to_latex_string(), "\\theoremstyle{remark}\n\\newtheorem*{remark}{Remark}");
+
+        let mut first = Theorem::new(&lemma)
+            .title("Zorn")
+            .label(Label::new(LabelKind::Theorem, "zorn"));
+        first.push(Element::from(Text::new("Every chain has a bound.", TextType::Normal)));
+        assert_eq!(
+            first.to_latex_string(),
+            "\\begin{lemma}[{Zorn}]\nEvery chain has a bound.\n\\label{thm:zorn}\n\\end{lemma}"
+        );
+        let mut proof = Proof::new();
+        proof.push(Element::from(Text::new("Obvious.", TextType::Normal)));
+
+        let mut list = ElementList::new(&Metadata::default());
+        list.push(Element::from(Package::new("hyperref")));
+        list.push(Element::from(first));
+        list.push(Element::from(Theorem::new(&theorem)));
+        list.push(Element::from(proof));
+        list.push(Element::from(Theorem::new(&remark)));
+        let latex = list.to_latex_string();
+        let position = |s: &str| latex.find(s).unwrap();
+        assert_eq!(latex.matches(r"\newtheorem{theorem}").count(), 1);
+        assert_eq!(latex.matches(r"\usepackage{amsthm}").count(), 1);
+        assert!(position(r"\usepackage{hyperref}") < position(r"\newtheorem{theorem}"));
+        assert!(position(r"\newtheorem{theorem}") < position(r"\newtheorem{lemma}"));
+        assert!(position(r"\newtheorem*{remark}") < position(r"\begin{document}"));
+        assert!(latex.contains("\\begin{proof}\nObvious.\n\\end{proof}"));
+        assert!(list.validate().is_empty());
+        assert!(list.package_conflicts().is_empty());
+
+        let proof = Proof::new().title("Proof of [1] & 50%");
+        assert_eq!(proof.to_latex_string(), "\\begin{proof}[{Proof of [1] \\& 50\\%}]\n\\end{proof}");
+        let cauchy = Theorem::new(&theorem).title("Cauchy–Schwarz for $L^2$").raw();
+        assert!(cauchy.to_latex_string().starts_with("\\begin{theorem}[{Cauchy–Schwarz for $L^2$}]"));
+        assert_eq!(Proof::new().title("$n = 1$").raw().to_latex_string(), "\\begin{proof}[{$n = 1$}]\n\\end{proof}");
+
+        // a kind declared twice with different numbering is declared once and reported
+        let mut list = ElementList::new(&Metadata::default());
+        list.push(Element::from(Theorem::new(&TheoremKind::lemma())));
+        list.push(Element::from(Theorem::new(&TheoremKind::lemma().within("section"))));
+        list.push(Element::from(Theorem::new(&TheoremKind::lemma())));
+        let latex = list.to_latex_string();
+        assert_eq!(latex.matches(r"\newtheorem{lemma}").count(), 1);
+        assert!(latex.contains("\\newtheorem{lemma}{Lemma}\n"));
+        assert_eq!(list.package_conflicts(), vec![
+            "environment `lemma`: declaration `\\theoremstyle{plain}\n\\newtheorem{lemma}{Lemma}[section]` conflicts with `\\theoremstyle{plain}\n\\newtheorem{lemma}{Lemma}`".to_string(),
+        ]);
+    }
+}
+
+testing! {
+    fn test_figures(){
+        use crate::bundle::figure::*;
+        use crate::bundle::graphicx::GraphicInclude;
+        let image = |path: &str| Element::from(GraphicInclude::new(PathBuf::from(path)));
+        let mut figure = Figure::new()
+            .placement(vec![Placement::Here, Placement::Top, Placement::Force])
+            .caption("Results of the run")
+            .short_caption("Results")
+            .label(Label::new(LabelKind::Figure, "results"));
+        figure.push(image("plot.png"));
+        assert_eq!(
+            figure.to_latex_string(),
+            "\\begin{figure}[ht!]\n\\centering\n\\includegraphics{plot.png}\n\\caption[{Results}]{Results of the run}\n\\label{fig:results}\n\\end{figure}"
+        );
+        let element = Element::from(figure);
+        assert_eq!(element.label(), Some(&Label::new(LabelKind::Figure, "results")));
+        assert_eq!(element.requirements(), vec![Package::new("graphicx")]);
+
+        let mut grid = Figure::new().placement(vec![Placement::Exact]).wide().columns(2);
+        for name in ["a", "b", "c"] {
+            grid.push_subfigure(
+                SubFigure::new(vec![image(&format!("{name}.png"))])
+                    .caption(name)
+                    .label(Label::new(LabelKind::Figure, name)),
+            );
+        }
+        let latex = grid.to_latex_string();
+        assert!(latex.starts_with("\\begin{figure*}[H]\n\\centering\n\\begin{subfigure}[b]{0.47\\textwidth}"));
+        assert_eq!(latex.matches("\\hfill").count(), 1);
+        assert!(latex.contains("\\caption{b}\n\\label{fig:b}\n\\end{subfigure}\n\n\\begin{subfigure}"));
+        let requirements = Element::from(grid).requirements();
+        assert!(requirements.contains(&Package::new("subcaption")));
+        assert!(requirements.contains(&Package::new("float")));
+        assert!(requirements.contains(&Package::new("graphicx")));
+
+        let escaped = Figure::new().caption("Speed in m/s_2 & 50%").short_caption("Speed [m/s]");
+        assert!(escaped.to_latex_string().contains("\\caption[{Speed [m/s]}]{Speed in m/s\\_2 \\& 50\\%}"));
+        let raw = Figure::new().caption("$x^2$ over time").raw();
+        assert!(raw.to_latex_string().contains("\\caption{$x^2$ over time}"));
+        let mut panels = Figure::new();
+        panels.push_subfigure(SubFigure::new(Vec::new()).caption("#1"));
+        panels.push_subfigure(SubFigure::new(Vec::new()).caption("#2").raw());
+        let latex = panels.to_latex_string();
+        assert!(latex.contains("\\caption{\\#1}") && latex.contains("\\caption{#2}"));
+    }
+}
+
+testing! {
+    fn test_image_assets(){
+        use crate::bundle::graphicx::*;
+        use std::fs::{create_dir_all, write};
+        let root = std::env::temp_dir().join("texcore_assets");
+        create_dir_all(root.join("images")).unwrap();
+        // 1500 by 300 pixels at 150 dpi
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend(1500u32.to_be_bytes());
+        png.extend(300u32.to_be_bytes());
+        png.extend([8, 6, 0, 0, 0, 0, 0, 0, 0]);
+        png.extend(b"\0\0\0\x09pHYs");
+        png.extend(5906u32.to_be_bytes());
+        png.extend(5906u32.to_be_bytes());
+        png.extend([1, 0, 0, 0, 0]);
+        png.extend(b"\0\0\0\0IEND\0\0\0\0");
+        write(root.join("images/wide.png"), &png).unwrap();
+        // 900 by 600 pixels at 300 dpi
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0, 16];
+        jpeg.extend(b"JFIF\0\x01\x01\x01");
+        jpeg.extend([1, 44, 1, 44, 0, 0]);
+        jpeg.extend([0xFF, 0xC0, 0, 17, 8, 2, 88, 3, 132]);
+        write(root.join("photo.jpg"), &jpeg).unwrap();
+        write(root.join("images/chart.pdf"), "%PDF-1.4\n1 0 obj << /MediaBox [0 0 595 842] >>").unwrap();
+        write(root.join("images/logo.svg"), "<svg/>").unwrap();
+
+        let wide = image_size(&root.join("images/wide.png")).unwrap();
+        assert!((wide.width - 720.0).abs() < 0.1 && (wide.height - 144.0).abs() < 0.1);
+        assert_eq!(image_size(&root.join("photo.jpg")).unwrap(), ImageSize::new(216.0, 144.0));
+        assert_eq!(image_size(&root.join("images/chart.pdf")).unwrap(), ImageSize::new(595.0, 842.0));
+        assert!(image_size(&root.join("images/logo.svg")).is_err());
+
+        let include = |path: &str| GraphicInclude::new(PathBuf::from(path));
+        assert_eq!(
+            include("wide.png").fit_text_width(wide, 345.0).to_latex_string(),
+            r"\includegraphics[width=1\textwidth]{wide.png}"
+        );
+        assert!(include("wide.png").fit(wide, ImageSize::new(800.0, 600.0)).options().is_empty());
+
+        let mut list = ElementList::new(&Metadata::default());
+        list.push_array(graphics_bundle(PathBuf::from("images/"), vec![
+            include("wide.png"),
+            include("photo.jpg"),
+            include("chart"),
+            include("logo.svg"),
+            include("missing.png"),
+            GraphicInclude::with_options(PathBuf::from("wide"), GraphicOptions::new().scale(0.3)),
+        ]));
+        assert_eq!(list.graphics_paths(), vec![PathBuf::from("images/")]);
+        let assets = list.image_assets(&root, Engine::PdfLatex);
+        assert_eq!(assets[2], ("chart".to_string(), Some(root.join("images/chart.pdf"))));
+        assert_eq!(list.check_assets(&root, Engine::PdfLatex, Some(ImageSize::new(600.0, 800.0))), vec![
+            Diagnostic::OversizedImage("wide.png".to_string(), wide),
+            Diagnostic::OversizedImage("chart".to_string(), ImageSize::new(595.0, 842.0)),
+            Diagnostic::UnsupportedImage("logo.svg".to_string(), Engine::PdfLatex),
+            Diagnostic::MissingImage("missing.png".to_string()),
+        ]);
+        assert_eq!(list.check_assets(&root, Engine::Latex, None).len(), 6);
+    }
+}
+
+testing! {
+    fn test_table_spans(){
+        use crate::bundle::tables::*;
+        let text = |s: &str| Element::from(Text::new(s, TextType::Normal));
+        let columns = vec![
+            Column::new(Position::Left, Separator::Single),
+            Column::new(Position::Right, Separator::Single),
+            Column::new(Position::Right, Separator::None),
+            Column::new(Position::Right, Separator::Double),
+            Column::new(Position::Right, Separator::None),
+        ];
+        let rows = vec![
+            Row::from_cells(vec![
+                Cell::new(text("Region")).span_rows(2),
+                Cell::new(text("2023")).span_cols(2).align(Position::Centered),
+                Cell::new(text("2024")).span_cols(2).align(Position::Centered),
+            ]),
+            Row::new(Elements![text("Q1"), text("Q2"), text("Q1"), text("Q2")])
+                .rule(Rule::CLine(vec![(2, 3), (4, 5)])),
+            Row::new(Elements![text("North"), text("1"), text("2"), text("3"), text("4")]),
+            Row::from_cells(vec![Cell::new(text("Total")), Cell::empty().span_cols(4)])
+                .rule(Rule::None),
+        ];
+        let table = Table::try_new(None, columns.clone(), rows, false, false).unwrap();
+        assert_eq!(table.requirements(), vec![Package::new("multirow")]);
+        let latex = Element::from(table).latex;
+        assert!(latex.contains(
+            "\\hline\n\\multirow{2}{*}{Region} & \\multicolumn{2}{c||}{2023} & \\multicolumn{2}{c|}{2024} \\\\\n"
+        ));
+        assert!(latex.contains("\\cline{2-3}\\cline{4-5}\n & Q1 & Q2 & Q1 & Q2 \\\\\n"));
+        assert!(latex.contains("3 & 4 \\\\\nTotal & \\multicolumn{4}{r|}{} \\\\"));
+
+        let short = vec![Row::new(Elements![text("a"), text("b")])];
+        let error = Table::try_new(None, columns.clone(), short, false, false).unwrap_err();
+        assert_eq!(error.to_string(), "validation error: row 0 fills 2 columns instead of 5");
+        let open = vec![Row::from_cells(vec![Cell::new(text("a")).span_rows(2).span_cols(5)])];
+        assert!(Table::try_new(None, columns.clone(), open, false, false).is_err());
+
+        let overlap = vec![
+            Row::from_cells(vec![Cell::new(text("a")), Cell::new(text("b")).span_rows(2), Cell::new(text("c"))]),
+            Row::from_cells(vec![Cell::new(text("d")).span_cols(2), Cell::new(text("e"))]),
+        ];
+        let columns3 = columns[..3].to_vec();
+        let error = Table::try_new(None, columns3, overlap, false, false).unwrap_err();
+        assert_eq!(error.to_string(), "validation error: row 1 overlaps a cell spanning rows at column 1");
+
+        // a table built with `new()` is still checked by the list
+        let short = vec![Row::new(Elements![text("a"), text("b")])];
+        let mut list = ElementList::new(&Metadata::default());
+        list.push(Element::from(Table::new(None, columns, short, false, false)));
+        assert_eq!(list.validate(), vec![
+            Diagnostic::Invalid(0, "row 0 fills 2 columns instead of 5".to_string()),
+        ]);
+        assert!(list.check().is_err());
+    }
+}
+
+testing! {
+    fn test_booktabs(){
+        use crate::bundle::tables::*;
+        let text = |s: &str| Element::from(Text::new(s, TextType::Normal));
+        let columns = vec![
+            Column::new(Position::Left, Separator::Single),
+            Column::new(Position::Right, Separator::Single),
+            Column::new(Position::Right, Separator::Single),
+        ];
+        assert_eq!(columns.to_latex_string(), "| l | r | r |");
+        let open = vec![
+            Column::new(Position::Left, Separator::None),
+            Column::new(Position::Right, Separator::Double).right(Separator::Single),
+        ];
+        assert_eq!(open.to_latex_string(), "l || r |");
+
+        let rows = vec![
+            Row::from_cells(vec![Cell::empty(), Cell::new(text("Sales")).span_cols(2)]),
+            Row::new(Elements![text("Region"), text("2023"), text("2024")])
+                .rule(Rule::CLine(vec![(2, 3)])),
+            Row::new(Elements![text("North"), text("1"), text("2")]),
+            Row::new(Elements![text("South"), text("3"), text("4")]),
+        ];
+        let table = Table::new(None, columns, rows, false, false)
+            .style(TableStyle::Booktabs)
+            .header(2)
+            .striped("gray!10", "white");
+        assert_eq!(table.requirements(), vec![
+            Package::new("booktabs"),
+            Package::with_options("xcolor", vec![PackageOption::Flag("table".to_string())]),
+        ]);
+        let latex = Element::from(table).latex;
+        assert_eq!(latex, [
+            r"\rowcolors{3}{gray!10}{white}",
+            r"\begin{tabular}{l r r}",
+            r"\toprule",
+            r" & \multicolumn{2}{r}{Sales} \\",
+            r"\cmidrule(lr){2-3}",
+            r"Region & 2023 & 2024 \\",
+            r"\midrule",
+            r"North & 1 & 2 \\",
+            r"South & 3 & 4 \\",
+            r"\bottomrule",
+            r"\end{tabular}",
+        ].join("\n"));
+    }
+}
+
+testing! {
+    fn test_data_tables(){
+        use crate::bundle::tables::*;
+        let money = ColumnFormat::new().precision(2).thousands("{,}");
+        assert_eq!(money.apply("-1234567.891"), "-1{,}234{,}567.89");
+        assert_eq!(money.apply("12"), "12.00");
+        assert_eq!(ColumnFormat::new().apply("R&D 50%"), r"R\&D 50\%");
+
+        let header = vec!["Region".to_string(), "Revenue_total".to_string()];
+        let records = vec![
+            vec!["North & East".to_string(), "1500".to_string()],
+            vec!["South".to_string()],
+        ];
+        let table = Table::from_records(header, records, &[ColumnFormat::new(), money]);
+        let latex = Element::from(table).latex;
+        assert!(latex.starts_with("\\begin{tabular}{| l | r |}\n\\hline\nRegion & Revenue\\_total \\\\\n"));
+        assert!(latex.contains("North \\& East & 1{,}500.00 \\\\\n\\hline\nSouth &  \\\\"));
+    }
+}
+
+feature! {
+    "csv"
+    #[test]
+    fn test_csv_tables(){
+        use crate::bundle::tables::*;
+        use serde::Serialize;
+        let csv = "Quarter,Revenue\nQ1,\"1,200.5\"\n\"Q2, late\",980\n";
+        let table = Table::from_csv_reader(csv.as_bytes(), &[ColumnFormat::new(), ColumnFormat::new().precision(1)]).unwrap();
+        let latex = Element::from(table).latex;
+        // `1,200.5` isn't a number, so the column is left aligned and kept as is
+        assert!(latex.contains("{| l | l |}"));
+        assert!(latex.contains("Q2, late & 980.0 \\\\"));
+        // a record wider than the header pads the header
+        let ragged = Table::from_csv_reader("name,value\nx,1,extra".as_bytes(), &[]).unwrap();
+        assert!(ragged.check().is_ok());
+        assert!(Element::from(ragged).latex.contains("name & value &  \\\\\n"));
+
+        #[derive(Serialize)]
+        struct Quarter {
+            name: &'static str,
+            revenue: f64,
+        }
+        let quarters = vec![
+            Quarter { name: "Q1", revenue: 1200.0 },
+            Quarter { name: "Q2", revenue: 980.25 },
+        ];
+        let formats = [ColumnFormat::new(), ColumnFormat::new().precision(2).thousands(",")];
+        let latex = Element::from(Table::from_serialize(quarters, &formats).unwrap()).latex;
+        assert!(latex.contains("name & revenue \\\\"));
+        assert!(latex.contains("Q1 & 1,200.00 \\\\"));
+        assert!(latex.contains("{| l | r |}"));
+    }
+}
+
+testing! {
+    fn test_table_row_derive(){
+        use crate::bundle::tables::*;
+        #[derive(TableRow)]
+        struct QuarterlyResult {
+            #[tex(header = "Quarter & Year")]
+            quarter: String,
+            #[tex(header = "Revenue", align = "r", format = "{:.2}")]
+            revenue: f64,
+            #[tex(skip)]
+            #[allow(dead_code)]
+            notes: String,
+            #[tex(align = "c")]
+            audited: bool,
+        }
+        let results = vec![
+            QuarterlyResult { quarter: "Q1_2024".to_string(), revenue: 1200.5, notes: String::new(), audited: true },
+            QuarterlyResult { quarter: "Q2_2024".to_string(), revenue: 980.0, notes: String::new(), audited: false },
+        ];
+        assert_eq!(QuarterlyResult::headers(), vec!["Quarter & Year", "Revenue", "audited"]);
+        assert_eq!(QuarterlyResult::columns().to_latex_string(), "| l | r | c |");
+        assert_eq!(results[0].row().to_latex_string(), r"Q1\_2024 & 1200.50 & true \\");
+        let latex = Element::from(Table::from_rows(&results)).latex;
+        assert!(latex.starts_with("\\begin{tabular}{| l | r | c |}\n\\hline\nQuarter \\& Year & Revenue & audited \\\\"));
+        assert!(latex.contains(r"Q2\_2024 & 980.00 & false \\"));
+    }
+}
+
+testing! {
+    fn test_longtable(){
+        use crate::bundle::tables::*;
+        let text = |s: &str| Element::from(Text::new(s, TextType::Normal));
+        let columns = vec![
+            Column::new(Position::Left, Separator::Single),
+            Column::new(Position::Right, Separator::Single),
+        ];
+        let rows = vec![
+            Row::new(Elements![text("Item"), text("Count")]),
+            Row::new(Elements![text("a"), text("1")]),
+            Row::new(Elements![text("b"), text("2")]),
+        ];
+        let table = Table::new(None, columns.clone(), rows.clone(), false, false)
+            .header(1)
+            .long()
+            .continued("See next page")
+            .caption("Inventory")
+            .label(Label::new(LabelKind::Table, "inventory"));
+        assert!(table.requirements().contains(&Package::new("longtable")));
+        let element = Element::from(table);
+        assert_eq!(element.label(), Some(&Label::new(LabelKind::Table, "inventory")));
+        assert_eq!(element.latex, [
+            r"\begin{longtable}{| l | r |}",
+            r"\caption{Inventory}\label{tab:inventory} \\",
+            r"\hline",
+            r"Item & Count \\",
+            r"\hline",
+            r"\endfirsthead",
+            r"\caption[]{Inventory (continued)} \\",
+            r"\hline",
+            r"Item & Count \\",
+            r"\hline",
+            r"\endhead",
+            r"\hline",
+            r"\multicolumn{2}{|r|}{See next page} \\",
+            r"\endfoot",
+            r"\hline",
+            r"\endlastfoot",
+            r"a & 1 \\",
+            r"\hline",
+            r"b & 2 \\",
+            r"\end{longtable}",
+        ].join("\n"));
+
+        let booktabs = Table::new(None, columns.clone(), rows.clone(), false, false)
+            .style(TableStyle::Booktabs)
+            .header(1)
+            .long();
+        let latex = Element::from(booktabs).latex;
+        assert!(latex.starts_with("\\begin{longtable}{l r}\n\\toprule\nItem & Count \\\\\n\\midrule\n\\endhead\n\\midrule\n"));
+        assert!(latex.ends_with("\\bottomrule\n\\endlastfoot\na & 1 \\\\\nb & 2 \\\\\n\\end{longtable}"));
+
+        // the continued text is kept when set before `long()`, and both texts are escaped
+        let escaped = Table::new(None, columns.clone(), rows.clone(), false, false)
+            .continued("50% left")
+            .caption("Costs & fees")
+            .long();
+        let latex = Element::from(escaped).latex;
+        assert!(latex.contains(r"\caption{Costs \& fees} \\"));
+        assert!(latex.contains(r"\multicolumn{2}{|r|}{50\% left} \\"));
+
+        // a `tabular` can't place a caption or a label
+        let tabular = Table::new(None, columns, rows, false, false)
+            .caption("Inventory")
+            .label(Label::new(LabelKind::Table, "inventory"));
+        assert_eq!(tabular.check().unwrap_err().to_string(), [
+            "validation error: the caption of a `tabular` belongs to the float around it",
+            "the label of a table needs the caption of a long table",
+        ].join("\n"));
+        assert_eq!(Element::from(tabular).problems().len(), 2);
+    }
+}
@@ -0,0 +1,421 @@
+use crate::Level::*;
+use crate::Type::*;
+use crate::*;
+use serde::{Deserialize, Serialize};
+
+/// The sectioning commands of a document ordered from the outermost to the innermost
+#[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SectionLevel {
+    /// `\part{}`
+    Part,
+    /// `\chapter{}`
+    Chapter,
+    /// `\section{}`
+    Section,
+    /// `\subsection{}`
+    Subsection,
+    /// `\subsubsection{}`
+    Subsubsection,
+}
+
+impl SectionLevel {
+    /// Returns the heading element of this level with the given name
+    pub fn heading(&self, name: &str) -> Element<Any> {
+        match self {
+            SectionLevel::Part => Element::from(Part::new(name)),
+            SectionLevel::Chapter => Element::from(Chapter::new(name)),
+            SectionLevel::Section => Element::from(Header::new(name, 1)),
+            SectionLevel::Subsection => Element::from(Header::new(name, 2)),
+            SectionLevel::Subsubsection => Element::from(Header::new(name, 3)),
+        }
+    }
+    /// Returns the level of a heading element, or `None` if the element isn't a heading
+    pub fn of(element: &Element<Any>) -> Option<Self> {
+        match element.type_ {
+            T_Part => Some(SectionLevel::Part),
+            T_Chapter => Some(SectionLevel::Chapter),
+            T_Header => match element.value.header_level {
+                Some(0) | Some(1) | None => Some(SectionLevel::Section),
+                Some(2) => Some(SectionLevel::Subsection),
+                Some(_) => Some(SectionLevel::Subsubsection),
+            },
+            _ => None,
+        }
+    }
+}
+
+/// A sectioning unit that owns its heading, its body elements and its nested sections
+#[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub struct Section {
+    level: SectionLevel,
+    heading: Element<Any>,
+    elements: Vec<Element<Any>>,
+    children: Vec<Section>,
+}
+
+impl Section {
+    /// Creates a new empty section
+    pub fn new(level: SectionLevel, name: &str) -> Self {
+        Self {
+            level,
+            heading: level.heading(name),
+            elements: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+    /// Creates a new empty section from a heading element (`Part`, `Chapter` or `Header`)
+    pub fn from_heading(heading: Element<Any>) -> Option<Self> {
+        let level = SectionLevel::of(&heading)?;
+        Some(Self {
+            level,
+            heading,
+            elements: Vec::new(),
+            children: Vec::new(),
+        })
+    }
+    /// Returns the level of the section
+    pub fn level(&self) -> SectionLevel {
+        self.level
+    }
+    /// Returns the name of the section
+    pub fn name(&self) -> &str {
+        &self.heading.value.value
+    }
+    /// Returns the heading element of the section
+    pub fn heading(&self) -> &Element<Any> {
+        &self.heading
+    }
+    /// Returns a mutable reference to the heading element of the section
+    pub fn heading_mut(&mut self) -> &mut Element<Any> {
+        &mut self.heading
+    }
+    /// Returns the body elements of the section
+    pub fn elements(&self) -> &Vec<Element<Any>> {
+        &self.elements
+    }
+    /// Returns a mutable reference to the body elements of the section
+    pub fn elements_mut(&mut self) -> &mut Vec<Element<Any>> {
+        &mut self.elements
+    }
+    /// Returns the nested sections
+    pub fn children(&self) -> &Vec<Section> {
+        &self.children
+    }
+    /// Pushes an element to the end of the section's body
+    pub fn push(&mut self, element: Element<Any>) {
+        self.elements.push(element)
+    }
+    /// Pushes an element vector to the end of the section's body
+    pub fn push_array(&mut self, element_vec: Vec<Element<Any>>) {
+        self.elements.extend(element_vec)
+    }
+    /// Pushes a nested section after the existing ones.
+    ///
+    /// Fails if the section isn't deeper than `self`.
+    pub fn push_section(&mut self, section: Section) -> Result<(), Error> {
+        let index = self.children.len();
+        self.insert_section(index, section)
+    }
+    /// Inserts a nested section at `index`.
+    ///
+    /// Fails if the section isn't deeper than `self` or `index` is out of bounds.
+    pub fn insert_section(&mut self, index: usize, section: Section) -> Result<(), Error> {
+        if !self.accepts(index, section.level) {
+            return Err(misplaced(&section, &[index]));
+        }
+        self.children.insert(index, section);
+        Ok(())
+    }
+    /// Removes and returns the nested section at `index`
+    pub fn remove_section(&mut self, index: usize) -> Option<Section> {
+        if index < self.children.len() {
+            Some(self.children.remove(index))
+        } else {
+            None
+        }
+    }
+    /// Returns the nested section following the path of indices
+    pub fn section(&self, path: &[usize]) -> Option<&Section> {
+        match path.split_first() {
+            None => Some(self),
+            Some((first, rest)) => self.children.get(*first)?.section(rest),
+        }
+    }
+    /// Returns a mutable reference to the nested section following the path of indices
+    pub fn section_mut(&mut self, path: &[usize]) -> Option<&mut Section> {
+        match path.split_first() {
+            None => Some(self),
+            Some((first, rest)) => self.children.get_mut(*first)?.section_mut(rest),
+        }
+    }
+    /// Flattens the section into its heading, its body and its nested sections in order
+    pub fn to_elements(&self) -> Vec<Element<Any>> {
+        let mut vec = vec![self.heading.clone()];
+        vec.extend(self.elements.iter().cloned());
+        for child in &self.children {
+            vec.extend(child.to_elements())
+        }
+        vec
+    }
+    // whether a section of `level` can be inserted as the child at `index`
+    fn accepts(&self, index: usize, level: SectionLevel) -> bool {
+        level > self.level && index <= self.children.len()
+    }
+    // depth-first search of the path to the first section named `name`
+    fn find(&self, name: &str) -> Option<Vec<usize>> {
+        if self.name() == name {
+            return Some(Vec::new());
+        }
+        find_in(&self.children, name)
+    }
+}
+
+impl Tex for Section {
+    fn to_latex_string(&self) -> String {
+        let mut strings = Vec::new();
+        for e in self.to_elements() {
+            strings.push(e.latex)
+        }
+        strings.join("\n")
+    }
+}
+
+/// A document represented as a tree of sections instead of a flat list of elements.
+///
+/// Sections are addressed by a path of zero based indices, so the section numbered
+/// `3.2` in a document without parts or chapters is found at `&[2, 1]`.
+#[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub struct DocumentTree {
+    metadata: Metadata,
+    preamble: Vec<Element<Any>>,
+    elements: Vec<Element<Any>>,
+    sections: Vec<Section>,
+    #[serde(default)]
+    bibliography: Option<Bibliography>,
+}
+
+impl DocumentTree {
+    /// Creates a new empty tree
+    pub fn new(metadata: &Metadata) -> Self {
+        Self {
+            metadata: metadata.to_owned(),
+            preamble: Vec::new(),
+            elements: Vec::new(),
+            sections: Vec::new(),
+            bibliography: None,
+        }
+    }
+    /// returns the tree's metadata
+    pub fn metadata(&self) -> Metadata {
+        self.metadata.clone()
+    }
+    /// Changes the metadata
+    pub fn change_metadata(&mut self, metadata: Metadata) {
+        self.metadata = metadata
+    }
+    /// Attaches the database that citations are checked against
+    pub fn set_bibliography(&mut self, bibliography: Bibliography) {
+        self.bibliography = Some(bibliography)
+    }
+    /// returns the attached bibliography
+    pub fn bibliography(&self) -> Option<&Bibliography> {
+        self.bibliography.as_ref()
+    }
+    /// Returns the `Meta` and `Packages` level elements
+    pub fn preamble(&self) -> &Vec<Element<Any>> {
+        &self.preamble
+    }
+    /// Returns the `Document` level elements placed before the first section
+    pub fn elements(&self) -> &Vec<Element<Any>> {
+        &self.elements
+    }
+    /// Returns the top level sections
+    pub fn sections(&self) -> &Vec<Section> {
+        &self.sections
+    }
+    /// Pushes an element to the preamble if it is at the `Meta` or `Packages` level,
+    /// otherwise to the body before the first section
+    pub fn push(&mut self, element: Element<Any>) {
+        match element.level {
+            Meta | Packages => self.preamble.push(element),
+            Document => self.elements.push(element),
+        }
+    }
+    /// Pushes an element vector using `push()`
+    pub fn push_array(&mut self, element_vec: Vec<Element<Any>>) {
+        for element in element_vec {
+            self.push(element)
+        }
+    }
+    /// Pushes a top level section after the existing ones
+    pub fn push_section(&mut self, section: Section) {
+        self.sections.push(section)
+    }
+    /// Returns the section following the path of indices
+    pub fn section(&self, path: &[usize]) -> Option<&Section> {
+        let (first, rest) = path.split_first()?;
+        self.sections.get(*first)?.section(rest)
+    }
+    /// Returns a mutable reference to the section following the path of indices
+    pub fn section_mut(&mut self, path: &[usize]) -> Option<&mut Section> {
+        let (first, rest) = path.split_first()?;
+        self.sections.get_mut(*first)?.section_mut(rest)
+    }
+    /// Returns the path of the first section named `name` in document order
+    pub fn find(&self, name: &str) -> Option<Vec<usize>> {
+        find_in(&self.sections, name)
+    }
+    /// Inserts a section so that it ends up at `path`.
+    ///
+    /// Fails if the parent doesn't exist, the index is out of bounds
+    /// or the section isn't deeper than its parent.
+    pub fn insert_section(&mut self, path: &[usize], section: Section) -> Result<(), Error> {
+        if !self.accepts(path, section.level) {
+            return Err(misplaced(&section, path));
+        }
+        match path.split_last() {
+            Some((index, [])) => self.sections.insert(*index, section),
+            Some((index, parent)) => {
+                if let Some(p) = self.section_mut(parent) {
+                    p.children.insert(*index, section)
+                }
+            }
+            None => {}
+        }
+        Ok(())
+    }
+    /// Removes and returns the section at `path` along with everything it contains
+    pub fn remove_section(&mut self, path: &[usize]) -> Option<Section> {
+        match path.split_last()? {
+            (index, []) => {
+                if *index < self.sections.len() {
+                    Some(self.sections.remove(*index))
+                } else {
+                    None
+                }
+            }
+            (index, parent) => self.section_mut(parent)?.remove_section(*index),
+        }
+    }
+    /// Moves the section at `from` so that it ends up at `to`, where `to` is
+    /// resolved after the section has been removed.
+    ///
+    /// Fails and leaves the tree unchanged if the section can't be moved.
+    pub fn move_section(&mut self, from: &[usize], to: &[usize]) -> Result<(), Error> {
+        let section = match self.remove_section(from) {
+            None => {
+                let msg = format!("no section at {from:?}");
+                return Err(Error::Validation(msg));
+            }
+            Some(s) => s,
+        };
+        if self.accepts(to, section.level) {
+            self.insert_section(to, section)
+        } else {
+            let err = misplaced(&section, to);
+            // the original position is always valid once the section is taken out
+            self.insert_section(from, section)?;
+            Err(err)
+        }
+    }
+    // whether a section of `level` can be inserted so that it ends up at `path`
+    fn accepts(&self, path: &[usize], level: SectionLevel) -> bool {
+        match path.split_last() {
+            None => false,
+            Some((index, [])) => *index <= self.sections.len(),
+            Some((index, parent)) => self
+                .section(parent)
+                .is_some_and(|p| p.accepts(*index, level)),
+        }
+    }
+    /// Flattens the tree into an `ElementList`
+    pub fn to_element_list(&self) -> ElementList<Any> {
+        let mut list = ElementList::new(&self.metadata);
+        list.push_array(self.preamble.clone());
+        list.push_array(self.elements.clone());
+        for section in &self.sections {
+            list.push_array(section.to_elements())
+        }
+        if let Some(bibliography) = &self.bibliography {
+            list.set_bibliography(bibliography.clone())
+        }
+        list
+    }
+}
+
+impl Tex for DocumentTree {
+    fn to_latex_string(&self) -> String {
+        self.to_element_list().to_latex_string()
+    }
+}
+
+impl From<DocumentTree> for ElementList<Any> {
+    fn from(value: DocumentTree) -> Self {
+        value.to_element_list()
+    }
+}
+
+impl From<ElementList<Any>> for DocumentTree {
+    fn from(value: ElementList<Any>) -> Self {
+        let mut tree = DocumentTree::new(&value.metadata());
+        tree.bibliography = value.bibliography().cloned();
+        // the chain of sections that are still open, from the outermost to the innermost
+        let mut open: Vec<Section> = Vec::new();
+        for element in value.list_to_array() {
+            if element.level != Document {
+                tree.preamble.push(element);
+                continue;
+            }
+            match SectionLevel::of(&element) {
+                Some(level) => {
+                    while open.last().is_some_and(|s| s.level >= level) {
+                        close_last(&mut tree, &mut open);
+                    }
+                    open.push(Section {
+                        level,
+                        heading: element,
+                        elements: Vec::new(),
+                        children: Vec::new(),
+                    })
+                }
+                None => match open.last_mut() {
+                    Some(s) => s.push(element),
+                    None => tree.elements.push(element),
+                },
+            }
+        }
+        while !open.is_empty() {
+            close_last(&mut tree, &mut open);
+        }
+        tree
+    }
+}
+
+fn misplaced(section: &Section, path: &[usize]) -> Error {
+    let msg = format!(
+        "section `{}` ({:?}) can't be placed at {path:?}",
+        section.name(),
+        section.level
+    );
+    Error::Validation(msg)
+}
+
+// Pops the innermost open section and attaches it to its parent
+fn close_last(tree: &mut DocumentTree, open: &mut Vec<Section>) {
+    if let Some(section) = open.pop() {
+        match open.last_mut() {
+            Some(parent) => parent.children.push(section),
+            None => tree.sections.push(section),
+        }
+    }
+}
+
+fn find_in(sections: &[Section], name: &str) -> Option<Vec<usize>> {
+    for (i, section) in sections.iter().enumerate() {
+        if let Some(mut path) = section.find(name) {
+            path.insert(0, i);
+            return Some(path);
+        }
+    }
+    None
+}
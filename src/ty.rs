@@ -1,4 +1,4 @@
-use crate::{Element, Level, Tex};
+use crate::{Element, Level, Requirements, Tex};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use texcore_traits::*;
@@ -20,18 +20,27 @@ pub enum Type {
     T_Custom,
     T_Comment,
     T_Bundle,
+    T_Reference,
+    T_Citation,
+    T_Bibliography,
 }
 
-/// Represents the metadata
+/// Represents the metadata, where every field left as `None` is omitted from the output
 #[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
 pub struct Metadata {
-    pub author: String,
-    pub date: String,
-    pub title: String,
-    pub fontsize: u8,
-    pub papersize: String,
+    pub author: Option<String>,
+    pub date: Option<String>,
+    pub title: Option<String>,
+    pub fontsize: Option<u8>,
+    pub papersize: Option<String>,
     pub doc_class: String,
+    /// Class options placed in order after the font and paper size
+    #[serde(default)]
+    pub options: Vec<ClassOption>,
     pub maketitle: bool,
+    /// Inserts `title` and `author` as trusted LaTeX instead of escaping them
+    #[serde(default)]
+    pub(crate) raw: bool,
 }
 
 impl Metadata {
@@ -45,13 +54,182 @@ impl Metadata {
         maketitle: bool,
     ) -> Self {
         Self {
-            author: author.to_string(),
-            date: date.to_string(),
-            title: title.to_string(),
-            fontsize,
-            papersize: papersize.to_string(),
+            author: Some(author.to_string()),
+            date: Some(date.to_string()),
+            title: Some(title.to_string()),
+            fontsize: Some(fontsize),
+            papersize: Some(papersize.to_string()),
             doc_class: doc_class.to_string(),
+            options: Vec::new(),
             maketitle,
+            raw: false,
+        }
+    }
+    /// Inserts `title` and `author` as trusted LaTeX instead of escaping them
+    pub fn raw(mut self) -> Self {
+        self.raw = true;
+        self
+    }
+    /// Creates a builder for an `article` with no other fields set
+    pub fn builder() -> MetadataBuilder {
+        MetadataBuilder::default()
+    }
+    /// Returns the options of `\documentclass[]` in order
+    pub fn class_options(&self) -> Vec<String> {
+        let mut options = Vec::new();
+        if let Some(size) = self.fontsize {
+            options.push(format!("{size}pt"))
+        }
+        if let Some(paper) = &self.papersize {
+            options.push(paper.to_string())
+        }
+        for option in &self.options {
+            options.push(option.to_latex_string())
+        }
+        options
+    }
+}
+
+/// A builder for `Metadata`
+#[derive(Debug, Clone)]
+pub struct MetadataBuilder {
+    metadata: Metadata,
+}
+
+impl Default for MetadataBuilder {
+    fn default() -> Self {
+        Self {
+            metadata: Metadata {
+                author: None,
+                date: None,
+                title: None,
+                fontsize: None,
+                papersize: None,
+                doc_class: "article".to_string(),
+                options: Vec::new(),
+                maketitle: false,
+                raw: false,
+            },
+        }
+    }
+}
+
+impl MetadataBuilder {
+    /// Sets the document class
+    pub fn doc_class(mut self, doc_class: &str) -> Self {
+        self.metadata.doc_class = doc_class.to_string();
+        self
+    }
+    /// Sets the author
+    pub fn author(mut self, author: &str) -> Self {
+        self.metadata.author = Some(author.to_string());
+        self
+    }
+    /// Sets the date
+    pub fn date(mut self, date: &str) -> Self {
+        self.metadata.date = Some(date.to_string());
+        self
+    }
+    /// Sets the title
+    pub fn title(mut self, title: &str) -> Self {
+        self.metadata.title = Some(title.to_string());
+        self
+    }
+    /// Sets the font size in points
+    pub fn fontsize(mut self, fontsize: u8) -> Self {
+        self.metadata.fontsize = Some(fontsize);
+        self
+    }
+    /// Sets the paper size, like `a4paper`
+    pub fn papersize(mut self, papersize: &str) -> Self {
+        self.metadata.papersize = Some(papersize.to_string());
+        self
+    }
+    /// Appends a class option
+    pub fn option(mut self, option: ClassOption) -> Self {
+        self.metadata.options.push(option);
+        self
+    }
+    /// Appends a vector of class options
+    pub fn options(mut self, options: Vec<ClassOption>) -> Self {
+        self.metadata.options.extend(options);
+        self
+    }
+    /// Sets whether `\maketitle` is placed at the beginning of the document
+    pub fn maketitle(mut self, maketitle: bool) -> Self {
+        self.metadata.maketitle = maketitle;
+        self
+    }
+    /// Sets whether `title` and `author` are trusted LaTeX
+    pub fn raw(mut self, raw: bool) -> Self {
+        self.metadata.raw = raw;
+        self
+    }
+    /// Returns the built `Metadata`
+    pub fn build(self) -> Metadata {
+        self.metadata
+    }
+}
+
+/// Represents the options of `\documentclass[]`
+#[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub enum ClassOption {
+    OneColumn,
+    TwoColumn,
+    OneSide,
+    TwoSide,
+    Draft,
+    Final,
+    Landscape,
+    OpenAny,
+    OpenRight,
+    TitlePage,
+    NoTitlePage,
+    Fleqn,
+    Leqno,
+    /// Any other option such as `aspectratio=169` for `beamer`
+    Custom(String),
+}
+
+impl Tex for ClassOption {
+    fn to_latex_string(&self) -> String {
+        match self {
+            ClassOption::OneColumn => "onecolumn",
+            ClassOption::TwoColumn => "twocolumn",
+            ClassOption::OneSide => "oneside",
+            ClassOption::TwoSide => "twoside",
+            ClassOption::Draft => "draft",
+            ClassOption::Final => "final",
+            ClassOption::Landscape => "landscape",
+            ClassOption::OpenAny => "openany",
+            ClassOption::OpenRight => "openright",
+            ClassOption::TitlePage => "titlepage",
+            ClassOption::NoTitlePage => "notitlepage",
+            ClassOption::Fleqn => "fleqn",
+            ClassOption::Leqno => "leqno",
+            ClassOption::Custom(option) => option,
+        }
+        .to_string()
+    }
+}
+
+impl From<&str> for ClassOption {
+    fn from(value: &str) -> Self {
+        match value {
+            "onecolumn" => ClassOption::OneColumn,
+            "twocolumn" => ClassOption::TwoColumn,
+            "oneside" => ClassOption::OneSide,
+            "twoside" => ClassOption::TwoSide,
+            "draft" => ClassOption::Draft,
+            "final" => ClassOption::Final,
+            "landscape" => ClassOption::Landscape,
+            "openany" => ClassOption::OpenAny,
+            "openright" => ClassOption::OpenRight,
+            "titlepage" => ClassOption::TitlePage,
+            "notitlepage" => ClassOption::NoTitlePage,
+            "fleqn" => ClassOption::Fleqn,
+            "leqno" => ClassOption::Leqno,
+            _ => ClassOption::Custom(value.to_string()),
         }
     }
 }
@@ -99,6 +277,8 @@ pub struct Any {
     pub list_type: Option<ListType>,
     pub items: Option<Vec<Item>>,
     pub elements: Option<Vec<Element<Any>>>,
+    #[serde(default)]
+    pub package_options: Option<Vec<PackageOption>>,
     pub modified: bool,
 }
 
@@ -121,11 +301,51 @@ impl ExtraOptions for Environment {
             begin = option.modify(&begin);
         }
         let inner = self.inner_latex_string();
-        self.latex = vec![begin, inner, end].join("\n");
+        self.latex = [begin, inner, end].join("\n");
         self.modified = true;
     }
 }
 
+// Environments provided by packages
+const ENVIRONMENT_PACKAGES: [(&str, &str); 25] = [
+    ("equation*", "amsmath"),
+    ("align", "amsmath"),
+    ("align*", "amsmath"),
+    ("alignat", "amsmath"),
+    ("alignat*", "amsmath"),
+    ("gather", "amsmath"),
+    ("gather*", "amsmath"),
+    ("multline", "amsmath"),
+    ("multline*", "amsmath"),
+    ("flalign", "amsmath"),
+    ("flalign*", "amsmath"),
+    ("split", "amsmath"),
+    ("cases", "amsmath"),
+    ("matrix", "amsmath"),
+    ("pmatrix", "amsmath"),
+    ("bmatrix", "amsmath"),
+    ("Bmatrix", "amsmath"),
+    ("vmatrix", "amsmath"),
+    ("Vmatrix", "amsmath"),
+    ("proof", "amsthm"),
+    ("tabularx", "tabularx"),
+    ("longtable", "longtable"),
+    ("lstlisting", "listings"),
+    ("minted", "minted"),
+    ("subfigure", "subcaption"),
+];
+
+/// The package providing the environment, if it isn't built into LaTeX
+impl Requirements for Environment {
+    fn requirements(&self) -> Vec<Package> {
+        ENVIRONMENT_PACKAGES
+            .iter()
+            .filter(|(env, _)| *env == self.name)
+            .map(|(_, pkg)| Package::new(pkg))
+            .collect()
+    }
+}
+
 impl Environment {
     pub fn new(name: &str) -> Self {
         Self {
@@ -212,58 +432,141 @@ impl Input {
     }
 }
 
-/// Represents `\usepackage{}` in latex
-#[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize, ExtraOps)]
+/// Represents `\usepackage[options]{}` in latex
+#[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
 pub struct Package {
     pub pkg: String,
+    #[serde(default)]
+    pub options: Vec<PackageOption>,
     pub(crate) latex: String,
     pub(crate) modified: bool,
 }
 
+/// Square options are added to the package options, while curly options
+/// are appended to the latex string.
+impl ExtraOptions for Package {
+    fn modify_element(&mut self, options: Vec<Options>) {
+        let mut curly = Vec::new();
+        for option in options {
+            match option {
+                Options::Square(s) => self.options.push(PackageOption::from(s.as_str())),
+                _ => curly.push(option),
+            }
+        }
+        self.latex = self.to_latex_string();
+        for option in &curly {
+            self.latex = option.modify(&self.latex);
+        }
+        self.modified = !curly.is_empty();
+    }
+}
+
 impl Package {
     pub fn new(pkg: &str) -> Self {
         Self {
             pkg: pkg.to_string(),
+            options: Vec::new(),
             latex: String::new(),
             modified: false,
         }
     }
+    /// Creates a new package loaded with options
+    pub fn with_options(pkg: &str, options: Vec<PackageOption>) -> Self {
+        Self {
+            options,
+            ..Self::new(pkg)
+        }
+    }
+    /// Adds an option to the package
+    pub fn push_option(&mut self, option: PackageOption) {
+        self.options.push(option)
+    }
+}
+
+/// Represents an option of `\usepackage[]`, either a flag (`utf8`) or a key value pair (`margin=1in`)
+#[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
+pub enum PackageOption {
+    Flag(String),
+    Value(String, String),
+}
+
+impl PackageOption {
+    /// Returns the flag or the key of the option
+    pub fn key(&self) -> &str {
+        match self {
+            PackageOption::Flag(flag) => flag,
+            PackageOption::Value(key, _) => key,
+        }
+    }
+}
+
+impl From<&str> for PackageOption {
+    fn from(value: &str) -> Self {
+        match value.split_once('=') {
+            Some((key, value)) => {
+                PackageOption::Value(key.trim().to_string(), value.trim().to_string())
+            }
+            None => PackageOption::Flag(value.trim().to_string()),
+        }
+    }
 }
 
 /// Represents `\part{}` in latex
 #[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize, ExtraOps)]
 pub struct Part {
     pub name: String,
+    #[serde(default)]
+    pub(crate) raw: bool,
     pub(crate) latex: String,
     pub(crate) modified: bool,
 }
 
 impl Part {
+    /// Creates a new `Part`, escaping the LaTeX special characters in `name`
     pub fn new(name: &str) -> Self {
         Self {
             name: name.to_string(),
+            raw: false,
             latex: String::new(),
             modified: false,
         }
     }
+    /// Creates a new `Part` where `name` is trusted LaTeX inserted as is
+    pub fn raw(name: &str) -> Self {
+        Self {
+            raw: true,
+            ..Self::new(name)
+        }
+    }
 }
 
 /// Represents `\chapter{}` in latex
 #[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize, ExtraOps)]
 pub struct Chapter {
     pub name: String,
+    #[serde(default)]
+    pub(crate) raw: bool,
     pub(crate) latex: String,
     pub(crate) modified: bool,
 }
 
 impl Chapter {
+    /// Creates a new `Chapter`, escaping the LaTeX special characters in `name`
     pub fn new(name: &str) -> Self {
         Self {
             name: name.to_string(),
+            raw: false,
             latex: String::new(),
             modified: false,
         }
     }
+    /// Creates a new `Chapter` where `name` is trusted LaTeX inserted as is
+    pub fn raw(name: &str) -> Self {
+        Self {
+            raw: true,
+            ..Self::new(name)
+        }
+    }
 }
 
 /// Represents `\section{}` in latex
@@ -271,19 +574,30 @@ impl Chapter {
 pub struct Header {
     pub name: String,
     pub header_level: u8,
+    #[serde(default)]
+    pub(crate) raw: bool,
     pub(crate) latex: String,
     pub(crate) modified: bool,
 }
 
 impl Header {
+    /// Creates a new `Header`, escaping the LaTeX special characters in `name`
     pub fn new(name: &str, header_level: u8) -> Self {
         Self {
             name: name.to_string(),
             header_level,
+            raw: false,
             latex: String::new(),
             modified: false,
         }
     }
+    /// Creates a new `Header` where `name` is trusted LaTeX inserted as is
+    pub fn raw(name: &str, header_level: u8) -> Self {
+        Self {
+            raw: true,
+            ..Self::new(name, header_level)
+        }
+    }
 }
 
 /// Represents `\<text type>{}` in latex
@@ -291,37 +605,60 @@ impl Header {
 pub struct Text {
     pub content: String,
     pub type_: TextType,
+    #[serde(default)]
+    pub(crate) raw: bool,
     pub(crate) latex: String,
     pub(crate) modified: bool,
 }
 
 impl Text {
+    /// Creates a new `Text`, escaping the LaTeX special characters in `content`
+    /// unless the type is `TextType::Math`
     pub fn new(content: &str, type_: TextType) -> Self {
         Self {
             content: content.to_string(),
             type_,
+            raw: false,
             latex: String::new(),
             modified: false,
         }
     }
+    /// Creates a new `Text` where `content` is trusted LaTeX inserted as is
+    pub fn raw(content: &str, type_: TextType) -> Self {
+        Self {
+            raw: true,
+            ..Self::new(content, type_)
+        }
+    }
 }
 
 /// Represents `\paragraph{}` in latex
 #[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize, ExtraOps)]
 pub struct Paragraph {
     pub content: String,
+    #[serde(default)]
+    pub(crate) raw: bool,
     pub(crate) latex: String,
     pub(crate) modified: bool,
 }
 
 impl Paragraph {
+    /// Creates a new `Paragraph`, escaping the LaTeX special characters in `content`
     pub fn new(content: &str) -> Self {
         Self {
             content: content.to_string(),
+            raw: false,
             latex: String::new(),
             modified: false,
         }
     }
+    /// Creates a new `Paragraph` where `content` is trusted LaTeX inserted as is
+    pub fn raw(content: &str) -> Self {
+        Self {
+            raw: true,
+            ..Self::new(content)
+        }
+    }
 }
 
 /// Represents `\begin{list type} ... \end{list type}` in latex
@@ -348,12 +685,23 @@ impl List {
 #[derive(Debug, Clone, PartialOrd, PartialEq, Deserialize, Serialize)]
 pub struct Item {
     pub name: String,
+    #[serde(default)]
+    pub(crate) raw: bool,
 }
 
 impl Item {
+    /// Creates a new `Item`, escaping the LaTeX special characters in `name`
     pub fn new(name: &str) -> Self {
         Self {
             name: name.to_string(),
+            raw: false,
+        }
+    }
+    /// Creates a new `Item` where `name` is trusted LaTeX inserted as is
+    pub fn raw(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            raw: true,
         }
     }
 }
@@ -0,0 +1,279 @@
+use crate::bundle::graphicx::{Engine, ImageSize};
+use crate::bundle::math::{check_math_with, MathIssue};
+use crate::parse::{find_commands, read_control, read_group};
+use crate::*;
+use std::fmt::{Display, Formatter};
+
+// Commands referring to labels, where the `cleveref` ones accept a comma separated list
+const REFERENCES: [&str; 7] = [
+    "ref", "eqref", "pageref", "autoref", "nameref", "cref", "Cref",
+];
+
+// Commands citing entries, all accepting a comma separated list
+const CITATIONS: [&str; 18] = [
+    "cite",
+    "citep",
+    "citet",
+    "citeauthor",
+    "citeyear",
+    "nocite",
+    "parencite",
+    "Parencite",
+    "textcite",
+    "Textcite",
+    "autocite",
+    "Autocite",
+    "footcite",
+    "smartcite",
+    "fullcite",
+    "supercite",
+    "Cite",
+    "citetitle",
+];
+
+// Commands defining macros that math may use
+const DEFINITIONS: [&str; 4] = [
+    "newcommand",
+    "renewcommand",
+    "providecommand",
+    "DeclareMathOperator",
+];
+
+// Environments whose content is math
+const MATH_ENVIRONMENTS: [&str; 27] = [
+    "math",
+    "displaymath",
+    "equation",
+    "equation*",
+    "align",
+    "align*",
+    "gather",
+    "gather*",
+    "multline",
+    "multline*",
+    "alignat",
+    "alignat*",
+    "flalign",
+    "flalign*",
+    "split",
+    "aligned",
+    "gathered",
+    "cases",
+    "matrix",
+    "pmatrix",
+    "bmatrix",
+    "Bmatrix",
+    "vmatrix",
+    "Vmatrix",
+    "smallmatrix",
+    "array",
+    "subarray",
+];
+
+/// A problem found in a document before it is compiled
+#[derive(Debug, Clone, PartialOrd, PartialEq)]
+pub enum Diagnostic {
+    /// A label key that is defined more than once
+    DuplicateLabel(String),
+    /// A reference to a label key that isn't defined
+    DanglingReference(String),
+    /// A citation of a key that isn't in the attached bibliography
+    MissingCitation(String),
+    /// A problem in the math of the element at the index in the list
+    Math(usize, MathIssue),
+    /// A problem found when the element at the index in the list was built,
+    /// like a line with an alignment point in `gather`
+    Invalid(usize, String),
+    /// An included image that isn't found
+    MissingImage(String),
+    /// An included image in a format the engine can't read
+    UnsupportedImage(String, Engine),
+    /// An included image larger than the maximum size, with its intrinsic size in points
+    OversizedImage(String, ImageSize),
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Diagnostic::DuplicateLabel(key) => write!(f, "label `{key}` is defined more than once"),
+            Diagnostic::DanglingReference(key) => write!(f, "reference to undefined label `{key}`"),
+            Diagnostic::Math(index, issue) => write!(f, "element {index}: {issue}"),
+            Diagnostic::Invalid(index, problem) => write!(f, "element {index}: {problem}"),
+            Diagnostic::MissingCitation(key) => {
+                write!(f, "citation of `{key}` which isn't in the bibliography")
+            }
+            Diagnostic::MissingImage(path) => write!(f, "image `{path}` isn't found"),
+            Diagnostic::UnsupportedImage(path, engine) => {
+                write!(f, "image `{path}` can't be read by {}", engine.name())
+            }
+            Diagnostic::OversizedImage(path, size) => write!(
+                f,
+                "image `{path}` is {}pt by {}pt, larger than the maximum",
+                size.width, size.height
+            ),
+        }
+    }
+}
+
+impl Diagnostic {
+    /// Whether the problem may be a false alarm that doesn't stop `check()`,
+    /// like a command defined in a way the list doesn't show
+    pub fn is_warning(&self) -> bool {
+        matches!(self, Diagnostic::Math(_, MathIssue::UnknownCommand(..)))
+    }
+}
+
+impl ElementList<Any> {
+    /// Runs every check on the list and returns the problems found
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = self.check_elements();
+        diagnostics.extend(self.check_references());
+        diagnostics.extend(self.check_citations());
+        diagnostics.extend(self.check_math());
+        diagnostics
+    }
+    /// Returns the problems found when the elements were built
+    pub fn check_elements(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for (index, element) in self.iter().enumerate() {
+            for problem in element.problems() {
+                diagnostics.push(Diagnostic::Invalid(index, problem))
+            }
+        }
+        diagnostics
+    }
+    /// Returns an `Error::Validation` listing the problems found by `validate()`,
+    /// leaving out the warnings
+    pub fn check(&self) -> Result<(), Error> {
+        let mut diagnostics = self.validate();
+        diagnostics.retain(|d| !d.is_warning());
+        if diagnostics.is_empty() {
+            return Ok(());
+        }
+        let messages: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        Err(Error::Validation(messages.join("\n")))
+    }
+    /// Returns the duplicate labels and the references to undefined labels,
+    /// found in the latex of every element
+    pub fn check_references(&self) -> Vec<Diagnostic> {
+        let mut labels: Vec<&str> = Vec::new();
+        let mut references: Vec<&str> = Vec::new();
+        let mut diagnostics = Vec::new();
+        for element in self.iter() {
+            for (_, arg) in find_commands(&element.latex, &["label"]) {
+                let key = arg.trim();
+                if labels.contains(&key) {
+                    push_unique(
+                        &mut diagnostics,
+                        Diagnostic::DuplicateLabel(key.to_string()),
+                    );
+                }
+                labels.push(key)
+            }
+            for (_, arg) in find_commands(&element.latex, &REFERENCES) {
+                references.extend(arg.split(',').map(str::trim))
+            }
+        }
+        for key in references {
+            if !labels.contains(&key) {
+                push_unique(
+                    &mut diagnostics,
+                    Diagnostic::DanglingReference(key.to_string()),
+                );
+            }
+        }
+        diagnostics
+    }
+    /// Returns the citations of keys that aren't in the attached bibliography,
+    /// or nothing when no bibliography is attached
+    pub fn check_citations(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let bibliography = match self.bibliography() {
+            Some(bibliography) => bibliography,
+            None => return diagnostics,
+        };
+        for element in self.iter() {
+            for (_, arg) in find_commands(&element.latex, &CITATIONS) {
+                // `\nocite{*}` lists the whole database
+                for key in arg.split(',').map(str::trim).filter(|k| *k != "*") {
+                    if !bibliography.contains(key) {
+                        push_unique(
+                            &mut diagnostics,
+                            Diagnostic::MissingCitation(key.to_string()),
+                        );
+                    }
+                }
+            }
+        }
+        diagnostics
+    }
+    /// Returns the problems in the content of math text and math environments, also when they
+    /// are nested in a figure, a theorem or a table cell, in which case the index is the one
+    /// of the element holding them, accepting the commands defined in the list with
+    /// `\newcommand` and the like
+    pub fn check_math(&self) -> Vec<Diagnostic> {
+        let known: Vec<&str> = self.iter().flat_map(|e| defined(&e.latex)).collect();
+        let mut diagnostics = Vec::new();
+        for (index, element) in self.iter().enumerate() {
+            for math in math_of(element) {
+                for issue in check_math_with(math, &known) {
+                    diagnostics.push(Diagnostic::Math(index, issue))
+                }
+            }
+        }
+        diagnostics
+    }
+}
+
+// the math of the element or else of its nested elements, like the cells of a table,
+// where a math environment is checked as a whole
+fn math_of(element: &Element<Any>) -> Vec<&str> {
+    match element.type_ {
+        Type::T_Text if element.value.text_type == Some(TextType::Math) => {
+            vec![&element.value.value]
+        }
+        Type::T_Environment if MATH_ENVIRONMENTS.contains(&element.value.value.as_str()) => {
+            vec![&element.latex]
+        }
+        _ => {
+            let elements = element.value.elements.iter().flatten();
+            elements.flat_map(math_of).collect()
+        }
+    }
+}
+
+// the names of the commands defined in the latex, like `R` for `\newcommand{\R}` or `\newcommand*\R`
+fn defined(latex: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut i = 0;
+    while i < latex.len() {
+        match latex.as_bytes()[i] {
+            b'\\' => {
+                let (name, after) = read_control(latex, i);
+                i = after;
+                if !DEFINITIONS.contains(&name.trim_end_matches('*')) {
+                    continue;
+                }
+                let rest = &latex[after..];
+                let start = after + rest.len() - rest.trim_start().len();
+                if let Some((name, end)) = read_group(latex, start, b'{', b'}') {
+                    names.push(name.trim().trim_start_matches('\\'));
+                    i = end
+                } else if latex[start..].starts_with('\\') {
+                    let (name, end) = read_control(latex, start);
+                    names.push(name.trim_end_matches('*'));
+                    i = end
+                }
+            }
+            b'%' => i = latex[i..].find('\n').map_or(latex.len(), |n| i + n),
+            _ => i += 1,
+        }
+    }
+    names
+}
+
+fn push_unique(diagnostics: &mut Vec<Diagnostic>, diagnostic: Diagnostic) {
+    if !diagnostics.contains(&diagnostic) {
+        diagnostics.push(diagnostic)
+    }
+}
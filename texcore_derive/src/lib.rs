@@ -24,3 +24,111 @@ fn impl_my_trait(ast: &syn::DeriveInput) -> TokenStream {
     };
     gen.into()
 }
+
+/// Implements `texcore::bundle::tables::TableRow` for a struct with named fields,
+/// where each field is a column in declaration order.
+///
+/// Fields accept `#[tex(header = "Revenue", align = "r", format = "{:.2}")]`,
+/// where the alignment is one of `l`, `c` and `r`, and `#[tex(skip)]` to leave them out.
+#[proc_macro_derive(TableRow, attributes(tex))]
+pub fn table_row_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse(input).unwrap();
+    match impl_table_row(&ast) {
+        Ok(gen) => gen,
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn impl_table_row(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let fields = match &ast.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "`TableRow` can only be derived for structs with named fields",
+            ))
+        }
+    };
+    let mut headers = Vec::new();
+    let mut aligns = Vec::new();
+    let mut cells = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let mut header = ident.to_string();
+        let mut align = quote!(Left);
+        let mut format = syn::LitStr::new("{}", ident.span());
+        let mut skip = false;
+        for attr in field.attrs.iter().filter(|a| a.path.is_ident("tex")) {
+            let list = match attr.parse_meta()? {
+                syn::Meta::List(list) => list,
+                meta => return Err(syn::Error::new_spanned(meta, "expected `tex(...)`")),
+            };
+            for nested in list.nested {
+                match nested {
+                    syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("skip") => {
+                        skip = true
+                    }
+                    syn::NestedMeta::Meta(syn::Meta::NameValue(pair)) => {
+                        let value = match &pair.lit {
+                            syn::Lit::Str(value) => value.clone(),
+                            lit => return Err(syn::Error::new_spanned(lit, "expected a string")),
+                        };
+                        if pair.path.is_ident("header") {
+                            header = value.value()
+                        } else if pair.path.is_ident("format") {
+                            format = value
+                        } else if pair.path.is_ident("align") {
+                            align = match value.value().as_str() {
+                                "l" => quote!(Left),
+                                "c" => quote!(Centered),
+                                "r" => quote!(Right),
+                                _ => {
+                                    return Err(syn::Error::new_spanned(
+                                        value,
+                                        "expected `l`, `c` or `r`",
+                                    ))
+                                }
+                            }
+                        } else {
+                            return Err(syn::Error::new_spanned(
+                                pair.path,
+                                "expected `header`, `align`, `format` or `skip`",
+                            ));
+                        }
+                    }
+                    nested => {
+                        return Err(syn::Error::new_spanned(
+                            nested,
+                            "expected `header`, `align`, `format` or `skip`",
+                        ))
+                    }
+                }
+            }
+        }
+        if skip {
+            continue;
+        }
+        headers.push(header);
+        aligns.push(align);
+        cells.push(quote!(format!(#format, self.#ident)));
+    }
+    let gen = quote! {
+        impl #impl_generics texcore::bundle::tables::TableRow for #name #ty_generics #where_clause {
+            fn headers() -> Vec<String> {
+                vec![#(#headers.to_string()),*]
+            }
+            fn aligns() -> Vec<texcore::bundle::tables::Position> {
+                vec![#(texcore::bundle::tables::Position::#aligns),*]
+            }
+            fn cells(&self) -> Vec<String> {
+                vec![#(#cells),*]
+            }
+        }
+    };
+    Ok(gen.into())
+}
@@ -1,4 +1,4 @@
-pub use texcore_derive::ExtraOps;
+pub use texcore_derive::{ExtraOps, TableRow};
 
 /// A trait to modify an element to add extra options to it.
 pub trait ExtraOptions {